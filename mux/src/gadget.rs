@@ -0,0 +1,6 @@
+//! Gadgets built on top of the chips in the crate root.
+
+pub(crate) mod boolean;
+pub(crate) mod compare;
+pub(crate) mod cond_swap;
+pub(crate) mod decompose;
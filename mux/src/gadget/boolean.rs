@@ -0,0 +1,272 @@
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: boolean-instructions
+pub(crate) trait BooleanInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `a AND b`. Both inputs must be boolean.
+    fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `a OR b`. Both inputs must be boolean.
+    fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `a XOR b`. Both inputs must be boolean.
+    fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `NOT a`. The input must be boolean.
+    fn not(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: boolean-instructions
+
+// ANCHOR: boolean-config
+#[derive(Clone, Debug)]
+pub(crate) struct BooleanConfig {
+    advice: [Column<Advice>; 3],
+    s_and: Selector,
+    s_or: Selector,
+    s_xor: Selector,
+    s_not: Selector,
+}
+// ANCHOR_END: boolean-config
+
+// ANCHOR: boolean-chip
+pub(crate) struct BooleanChip<F: FieldExt> {
+    config: BooleanConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: boolean-chip
+
+// ANCHOR: boolean-chip-trait-impl
+impl<F: FieldExt> Chip<F> for BooleanChip<F> {
+    type Config = BooleanConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: boolean-chip-trait-impl
+
+// ANCHOR: boolean-chip-impl
+impl<F: FieldExt> BooleanChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        let s_and = meta.selector();
+        let s_or = meta.selector();
+        let s_xor = meta.selector();
+        let s_not = meta.selector();
+
+        let bool_check =
+            |value: Expression<F>| value.clone() * (Expression::Constant(F::one()) - value);
+
+        // Binary operations share a single-row layout:
+        //
+        // | a0 | a1 | a2  |
+        // |----|----|-----|
+        // | a  | b  | out |
+        let binary_gate =
+            |meta: &mut ConstraintSystem<F>,
+             name: &'static str,
+             selector: Selector,
+             op: fn(Expression<F>, Expression<F>) -> Expression<F>| {
+                meta.create_gate(name, |meta| {
+                    let a = meta.query_advice(advice[0], Rotation::cur());
+                    let b = meta.query_advice(advice[1], Rotation::cur());
+                    let out = meta.query_advice(advice[2], Rotation::cur());
+                    let s = meta.query_selector(selector);
+
+                    vec![
+                        s.clone() * bool_check(a.clone()),
+                        s.clone() * bool_check(b.clone()),
+                        s * (op(a, b) - out),
+                    ]
+                });
+            };
+
+        binary_gate(meta, "and", s_and, |a, b| a * b);
+        binary_gate(meta, "or", s_or, |a, b| a.clone() + b.clone() - a * b);
+        binary_gate(meta, "xor", s_xor, |a, b| {
+            a.clone() + b.clone() - Expression::Constant(F::from_u64(2)) * a * b
+        });
+
+        // | a0 | a1  |
+        // |----|-----|
+        // | a  | out |
+        meta.create_gate("not", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[1], Rotation::cur());
+            let s_not = meta.query_selector(s_not);
+
+            vec![
+                s_not.clone() * bool_check(a.clone()),
+                s_not * (Expression::Constant(F::one()) - a - out),
+            ]
+        });
+
+        BooleanConfig {
+            advice,
+            s_and,
+            s_or,
+            s_xor,
+            s_not,
+        }
+    }
+
+    /// Assigns `a`, `b` and `op(a, b)` in a single row gated by `selector`.
+    fn binary_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        selector: Selector,
+        a: Number<F>,
+        b: Number<F>,
+        op: fn(F, F) -> F,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "boolean op",
+            |mut region: Region<'_, F>| {
+                selector.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let b_cell = region.assign_advice(
+                    || "b",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+                region.constrain_equal(b.cell, b_cell)?;
+
+                let value = a.value.and_then(|a| b.value.map(|b| op(a, b)));
+                let cell = region.assign_advice(
+                    || "out",
+                    config.advice[2],
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: boolean-chip-impl
+
+// ANCHOR: boolean-instructions-impl
+impl<F: FieldExt> BooleanInstructions<F> for BooleanChip<F> {
+    type Num = Number<F>;
+
+    fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, self.config().s_and, a, b, |a, b| a * b)
+    }
+
+    fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, self.config().s_or, a, b, |a, b| a + b - a * b)
+    }
+
+    fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, self.config().s_xor, a, b, |a, b| {
+            a + b - F::from_u64(2) * a * b
+        })
+    }
+
+    fn not(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "not",
+            |mut region: Region<'_, F>| {
+                config.s_not.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+
+                let value = a.value.map(|a| F::one() - a);
+                let cell = region.assign_advice(
+                    || "out",
+                    config.advice[1],
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: boolean-instructions-impl
@@ -0,0 +1,198 @@
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{Number, RangeCheckChip, RangeCheckConfig, RangeCheckInstructions};
+
+// ANCHOR: compare-instructions
+pub(crate) trait CompareInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns 1 if `a > b`, and 0 otherwise. Both inputs are constrained to
+    /// fit in the chip's bit width.
+    fn greater_than(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: compare-instructions
+
+// ANCHOR: compare-config
+/// Configuration for comparing values that fit in `N` bits.
+#[derive(Clone, Debug)]
+pub(crate) struct CompareConfig<const N: usize> {
+    advice: [Column<Advice>; 3],
+    s_compare: Selector,
+    range_check_config: RangeCheckConfig<N>,
+}
+// ANCHOR_END: compare-config
+
+// ANCHOR: compare-chip
+pub(crate) struct CompareChip<F: FieldExt, const N: usize> {
+    config: CompareConfig<N>,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: compare-chip
+
+// ANCHOR: compare-chip-trait-impl
+impl<F: FieldExt, const N: usize> Chip<F> for CompareChip<F, N> {
+    type Config = CompareConfig<N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: compare-chip-trait-impl
+
+// ANCHOR: compare-chip-impl
+impl<F: FieldExt, const N: usize> CompareChip<F, N> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        let s_compare = meta.selector();
+        let range_check_config = RangeCheckChip::<F, N>::configure(meta, [advice[0], advice[1]]);
+
+        // `a - b - 1 + 2^N` lies in `[0, 2^{N+1})` for `N`-bit inputs, and its
+        // top bit is set exactly when `a > b`. We split it into that top bit
+        // and an `N`-bit remainder, which is range-checked separately:
+        //
+        // | a0  | a1 | a2  |
+        // |-----|----|-----|
+        // | a   | b  | top |
+        // | low |    |     |
+        meta.create_gate("greater than", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let top = meta.query_advice(advice[2], Rotation::cur());
+            let low = meta.query_advice(advice[0], Rotation::next());
+            let s_compare = meta.query_selector(s_compare);
+
+            let two_pow_n = Expression::Constant(Self::two_pow_n());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                s_compare.clone() * top.clone() * (one.clone() - top.clone()),
+                s_compare * (a - b - one + two_pow_n.clone() - top * two_pow_n - low),
+            ]
+        });
+
+        CompareConfig {
+            advice,
+            s_compare,
+            range_check_config,
+        }
+    }
+
+    fn two_pow_n() -> F {
+        (0..N).fold(F::one(), |acc, _| acc.double())
+    }
+}
+// ANCHOR_END: compare-chip-impl
+
+// ANCHOR: compare-instructions-impl
+impl<F: FieldExt, const N: usize> CompareInstructions<F> for CompareChip<F, N> {
+    type Num = Number<F>;
+
+    fn greater_than(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        // The gate only computes the right answer for `N`-bit inputs: outside
+        // that range, `a - b - 1 + 2^N` can wrap around the field modulus and
+        // still split into a boolean and an `N`-bit remainder.
+        let range_check_chip =
+            RangeCheckChip::<F, N>::construct(config.range_check_config.clone(), ());
+        range_check_chip.range_check(layouter.namespace(|| "range check a"), a.clone())?;
+        range_check_chip.range_check(layouter.namespace(|| "range check b"), b.clone())?;
+
+        let two_pow_n = Self::two_pow_n();
+        let diff = a
+            .value
+            .and_then(|a| b.value.map(|b| a - b - F::one() + two_pow_n));
+        let top = diff.map(|diff| {
+            let repr = diff.to_repr();
+            F::from_u64(((repr.as_ref()[N / 8] >> (N % 8)) & 1) as u64)
+        });
+        let low_value = diff.and_then(|diff| top.map(|top| diff - top * two_pow_n));
+
+        let mut out = None;
+        let mut low = None;
+        layouter.assign_region(
+            || "greater than",
+            |mut region: Region<'_, F>| {
+                config.s_compare.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let b_cell = region.assign_advice(
+                    || "b",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+                region.constrain_equal(b.cell, b_cell)?;
+
+                let top_cell = region.assign_advice(
+                    || "top",
+                    config.advice[2],
+                    0,
+                    || top.ok_or(Error::SynthesisError),
+                )?;
+                let low_cell = region.assign_advice(
+                    || "low",
+                    config.advice[0],
+                    1,
+                    || low_value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number {
+                    cell: top_cell,
+                    value: top,
+                });
+                low = Some(Number {
+                    cell: low_cell,
+                    value: low_value,
+                });
+                Ok(())
+            },
+        )?;
+
+        range_check_chip.range_check(layouter.namespace(|| "range check low"), low.unwrap())?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: compare-instructions-impl
@@ -0,0 +1,182 @@
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: cond-swap-instructions
+pub(crate) trait CondSwapInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `(a, b)` if `swap` is 0, and `(b, a)` if `swap` is 1.
+    fn conditional_swap(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        swap: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+}
+// ANCHOR_END: cond-swap-instructions
+
+// ANCHOR: cond-swap-config
+#[derive(Clone, Debug)]
+pub(crate) struct CondSwapConfig {
+    advice: [Column<Advice>; 3],
+    s_swap: Selector,
+}
+// ANCHOR_END: cond-swap-config
+
+// ANCHOR: cond-swap-chip
+pub(crate) struct CondSwapChip<F: FieldExt> {
+    config: CondSwapConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: cond-swap-chip
+
+// ANCHOR: cond-swap-chip-trait-impl
+impl<F: FieldExt> Chip<F> for CondSwapChip<F> {
+    type Config = CondSwapConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: cond-swap-chip-trait-impl
+
+// ANCHOR: cond-swap-chip-impl
+impl<F: FieldExt> CondSwapChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        let s_swap = meta.selector();
+
+        // Each output is a mux over the inputs, in opposite orders:
+        //
+        // | a0   | a1   | a2   |
+        // |------|------|------|
+        // | a    | b    | swap |
+        // | out0 | out1 |      |
+        meta.create_gate("cond swap", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let swap = meta.query_advice(advice[2], Rotation::cur());
+            let out0 = meta.query_advice(advice[0], Rotation::next());
+            let out1 = meta.query_advice(advice[1], Rotation::next());
+            let s_swap = meta.query_selector(s_swap);
+
+            vec![
+                // out0 = mux(a, b, swap)
+                s_swap.clone() * ((b.clone() - a.clone()) * swap.clone() + a.clone() - out0),
+                // out1 = mux(b, a, swap)
+                s_swap.clone() * ((a - b.clone()) * swap.clone() + b - out1),
+                // swap is boolean
+                s_swap * swap.clone() * (Expression::Constant(F::one()) - swap),
+            ]
+        });
+
+        CondSwapConfig { advice, s_swap }
+    }
+}
+// ANCHOR_END: cond-swap-chip-impl
+
+// ANCHOR: cond-swap-instructions-impl
+impl<F: FieldExt> CondSwapInstructions<F> for CondSwapChip<F> {
+    type Num = Number<F>;
+
+    fn conditional_swap(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        swap: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "cond swap",
+            |mut region: Region<'_, F>| {
+                config.s_swap.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let b_cell = region.assign_advice(
+                    || "b",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                let swap_cell = region.assign_advice(
+                    || "swap",
+                    config.advice[2],
+                    0,
+                    || swap.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+                region.constrain_equal(b.cell, b_cell)?;
+                region.constrain_equal(swap.cell, swap_cell)?;
+
+                let mux = |x: Option<F>, y: Option<F>| {
+                    x.and_then(|x| y.and_then(|y| swap.value.map(|swap| (y - x) * swap + x)))
+                };
+                let out0_value = mux(a.value, b.value);
+                let out1_value = mux(b.value, a.value);
+
+                let out0 = region.assign_advice(
+                    || "out0",
+                    config.advice[0],
+                    1,
+                    || out0_value.ok_or(Error::SynthesisError),
+                )?;
+                let out1 = region.assign_advice(
+                    || "out1",
+                    config.advice[1],
+                    1,
+                    || out1_value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some((
+                    Number {
+                        cell: out0,
+                        value: out0_value,
+                    },
+                    Number {
+                        cell: out1,
+                        value: out1_value,
+                    },
+                ));
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: cond-swap-instructions-impl
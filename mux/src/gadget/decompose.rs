@@ -0,0 +1,226 @@
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: decompose-instructions
+pub(crate) trait DecomposeInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns the `n` bits of `value` in little-endian order, constraining
+    /// each to be boolean and their weighted sum to equal `value`. `n` must be
+    /// a multiple of the chip's window.
+    fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error>;
+}
+// ANCHOR_END: decompose-instructions
+
+// ANCHOR: decompose-config
+#[derive(Clone, Debug)]
+pub(crate) struct DecomposeConfig {
+    /// One column per bit in a window, least significant first.
+    bits: Vec<Column<Advice>>,
+    /// The running sum of the windows decomposed so far.
+    acc: Column<Advice>,
+    s_first: Selector,
+    s_step: Selector,
+}
+// ANCHOR_END: decompose-config
+
+// ANCHOR: decompose-chip
+pub(crate) struct DecomposeChip<F: FieldExt> {
+    config: DecomposeConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: decompose-chip
+
+// ANCHOR: decompose-chip-trait-impl
+impl<F: FieldExt> Chip<F> for DecomposeChip<F> {
+    type Config = DecomposeConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: decompose-chip-trait-impl
+
+// ANCHOR: decompose-chip-impl
+impl<F: FieldExt> DecomposeChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the chip to decompose `bits.len()` bits per row.
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        bits: &[Column<Advice>],
+        acc: Column<Advice>,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(acc.into());
+        for column in bits {
+            meta.enable_equality((*column).into());
+        }
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+
+        // The windows are laid out from the most significant down, so that the
+        // running sum is shifted by a constant `2^W` at each step:
+        //
+        // | bits[0] .. bits[W-1] | acc                          |
+        // |----------------------|------------------------------|
+        // | top window           | acc_0 = window_0             |
+        // | next window          | acc_1 = 2^W * acc_0 + window |
+        // | ...                  | ...                          |
+        // | bottom window        | acc_last = value             |
+        let window_gate = |meta: &mut ConstraintSystem<F>,
+                           name: &'static str,
+                           selector: Selector,
+                           shift_prev: bool| {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let bits: Vec<_> = bits
+                    .iter()
+                    .map(|column| meta.query_advice(*column, Rotation::cur()))
+                    .collect();
+                let acc_cur = meta.query_advice(acc, Rotation::cur());
+
+                let one = Expression::Constant(F::one());
+                let two = Expression::Constant(F::from_u64(2));
+
+                // The window's value is `sum_j 2^j * bits[j]`, and the previous
+                // running sum is shifted by `2^W`.
+                let (window, shift) = bits.iter().fold(
+                    (Expression::Constant(F::zero()), one.clone()),
+                    |(window, shift), bit| {
+                        (window + shift.clone() * bit.clone(), shift * two.clone())
+                    },
+                );
+                let expected = if shift_prev {
+                    shift * meta.query_advice(acc, Rotation::prev()) + window
+                } else {
+                    window
+                };
+
+                let mut constraints: Vec<_> = bits
+                    .iter()
+                    .map(|bit| s.clone() * bit.clone() * (one.clone() - bit.clone()))
+                    .collect();
+                constraints.push(s * (expected - acc_cur));
+                constraints
+            });
+        };
+        window_gate(meta, "decompose first", s_first, false);
+        window_gate(meta, "decompose step", s_step, true);
+
+        DecomposeConfig {
+            bits: bits.to_vec(),
+            acc,
+            s_first,
+            s_step,
+        }
+    }
+}
+// ANCHOR_END: decompose-chip-impl
+
+// ANCHOR: decompose-instructions-impl
+impl<F: FieldExt> DecomposeInstructions<F> for DecomposeChip<F> {
+    type Num = Number<F>;
+
+    fn decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+
+        let window = config.bits.len();
+        if n == 0 || n % window != 0 {
+            return Err(Error::SynthesisError);
+        }
+
+        // Witness the low `n` bits of the value. If the value does not fit in
+        // `n` bits, their recomposition differs from it and the final equality
+        // constraint fails.
+        let bits: Vec<Option<F>> = (0..n)
+            .map(|i| {
+                value.value.map(|value| {
+                    let repr = value.to_repr();
+                    let byte = repr.as_ref().get(i / 8).copied().unwrap_or(0);
+                    F::from_u64(((byte >> (i % 8)) & 1) as u64)
+                })
+            })
+            .collect();
+
+        let mut out = vec![None; n];
+        layouter.assign_region(
+            || "decompose",
+            |mut region: Region<'_, F>| {
+                let mut acc = Some(F::zero());
+                let mut acc_cell = None;
+                for (row, low) in (0..n).step_by(window).rev().enumerate() {
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_step.enable(&mut region, row)?;
+                    }
+
+                    let mut window_value = Some(F::zero());
+                    let mut weight = F::one();
+                    for (j, column) in config.bits.iter().enumerate() {
+                        let i = low + j;
+                        let cell = region.assign_advice(
+                            || format!("bit {}", i),
+                            *column,
+                            row,
+                            || bits[i].ok_or(Error::SynthesisError),
+                        )?;
+                        out[i] = Some(Number {
+                            cell,
+                            value: bits[i],
+                        });
+                        window_value =
+                            window_value.and_then(|w| bits[i].map(|bit| w + weight * bit));
+                        weight = weight.double();
+                    }
+
+                    acc = acc.and_then(|acc| window_value.map(|w| acc * weight + w));
+                    acc_cell = Some(region.assign_advice(
+                        || format!("acc {}", row),
+                        config.acc,
+                        row,
+                        || acc.ok_or(Error::SynthesisError),
+                    )?);
+                }
+
+                region.constrain_equal(value.cell, acc_cell.unwrap())?;
+                Ok(())
+            },
+        )?;
+
+        Ok(out.into_iter().map(Option::unwrap).collect())
+    }
+}
+// ANCHOR_END: decompose-instructions-impl
@@ -0,0 +1,2563 @@
+//! A mux chip and gadgets built on it, and example circuits using them.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Chip, Layouter, Region, SimpleFloorPlanner},
+    pasta::{self, EqAffine},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, Expression, Fixed, Instance, ProvingKey, Selector, SingleVerifier,
+        VerifyingKey,
+    },
+    poly::{commitment::Params, Rotation},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+
+mod gadget;
+use gadget::boolean::{BooleanChip, BooleanConfig, BooleanInstructions};
+use gadget::compare::{CompareChip, CompareConfig, CompareInstructions};
+use gadget::cond_swap::{CondSwapChip, CondSwapConfig, CondSwapInstructions};
+use gadget::decompose::{DecomposeChip, DecomposeConfig, DecomposeInstructions};
+
+mod utils;
+use utils::{assign_or_default, const_cell, load_private_from_bytes, zero_cell};
+
+// ANCHOR: field-instructions
+/// A variable representing a number.
+#[derive(Clone)]
+pub struct Number<F: FieldExt> {
+    cell: Cell,
+    value: Option<F>,
+}
+
+/// Errors returned by [`FieldInstructions::select`].
+#[derive(Debug)]
+pub enum MuxError {
+    /// The condition's witness value is neither 0 nor 1.
+    NonBooleanCondition,
+    /// Any other error raised while synthesizing the mux.
+    Synthesis(Error),
+}
+
+impl From<Error> for MuxError {
+    fn from(error: Error) -> Self {
+        MuxError::Synthesis(error)
+    }
+}
+
+impl From<MuxError> for Error {
+    fn from(error: MuxError) -> Self {
+        match error {
+            MuxError::NonBooleanCondition => Error::SynthesisError,
+            MuxError::Synthesis(error) => error,
+        }
+    }
+}
+
+pub trait FieldInstructions<F: FieldExt>:
+    MuxInstructions<F> + NMuxInstructions<F> + IsZeroInstructions<F>
+{
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the given advice column as a private input.
+    fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        column: Column<Advice>,
+        a: Option<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Loads several numbers into consecutive rows of the given advice column,
+    /// within a single region.
+    fn load_private_many(
+        &self,
+        layouter: impl Layouter<F>,
+        column: Column<Advice>,
+        values: &[Option<F>],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error>;
+
+    /// Loads a constant into the given fixed column.
+    fn load_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        column: Column<Fixed>,
+        constant: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = (b - a) * c + a`.
+    fn mux(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        c: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `cond ? if_true : if_false`. A condition whose witness value is
+    /// not boolean is rejected with [`MuxError::NonBooleanCondition`] before
+    /// anything is assigned.
+    fn select(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cond: <Self as FieldInstructions<F>>::Num,
+        if_true: <Self as FieldInstructions<F>>::Num,
+        if_false: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, MuxError>;
+
+    /// Returns `(b - a) * c + a` for each `(a, b, c)` in `inputs`.
+    fn mux_batch(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[(
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+        )],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error>;
+
+    /// Returns `inputs[i]` for the single `i` at which `selectors` is 1. The
+    /// selectors must be boolean and sum to 1.
+    fn mux_n(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+        selectors: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `a` if `cond` is 1 and `b` if `cond` is 0, and exposes `cond` at
+    /// `audit_row` of the instance column so the verifier sees the branch taken.
+    fn audited_branch(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cond: <Self as FieldInstructions<F>>::Num,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        audit_row: usize,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `(found, index)` for the first element of `values` equal to
+    /// `target`. If there is no such element, both are 0.
+    fn find_first_matching(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+        target: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<
+        (
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+        ),
+        Error,
+    >;
+
+    /// Returns `registers` with the register at `write_addr_bits` (little-endian)
+    /// replaced by `value` if `write_enable` is 1, and unchanged otherwise. The
+    /// address bits and `write_enable` must be boolean.
+    fn update_register(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        registers: &[<Self as FieldInstructions<F>>::Num],
+        write_addr_bits: &[<Self as FieldInstructions<F>>::Num],
+        write_enable: <Self as FieldInstructions<F>>::Num,
+        value: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error>;
+
+    /// Returns 1 if `a` is zero, and 0 otherwise.
+    fn is_zero(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+// ANCHOR_END: field-instructions
+
+// ANCHOR: mux-instructions
+pub trait MuxInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `d = (b - a) * c + a`.
+    fn do_mux(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `(b - a) * c + a` for each `(a, b, c)` in `inputs`, laid out in a
+    /// single region.
+    fn do_mux_batch(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[(Self::Num, Self::Num, Self::Num)],
+    ) -> Result<Vec<Self::Num>, Error>;
+}
+// ANCHOR_END: mux-instructions
+
+// ANCHOR: n-mux-instructions
+pub trait NMuxInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `out = sum_i selectors[i] * inputs[i]` for one-hot `selectors`.
+    fn do_mux_n(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+        selectors: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: n-mux-instructions
+
+// ANCHOR: is-zero-instructions
+pub trait IsZeroInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `out = 1` if `a = 0`, and `out = 0` otherwise.
+    fn do_is_zero(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: is-zero-instructions
+
+// ANCHOR: subtract-instructions
+trait SubtractInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a - b`.
+    fn do_subtract(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: subtract-instructions
+
+// ANCHOR: equality-instructions
+trait EqualityInstructions<F: FieldExt>: SubtractInstructions<F> + IsZeroInstructions<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns 1 if `a == b`, and 0 otherwise. The output is boolean by
+    /// construction of the `is_zero` gates.
+    fn equals(
+        &self,
+        layouter: impl Layouter<F>,
+        a: <Self as EqualityInstructions<F>>::Num,
+        b: <Self as EqualityInstructions<F>>::Num,
+    ) -> Result<<Self as EqualityInstructions<F>>::Num, Error>;
+}
+// ANCHOR_END: equality-instructions
+
+// ANCHOR: range-check-instructions
+trait RangeCheckInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Constrains `value` to fit in the chip's bit width, returning its bits in
+    /// little-endian order. The bit width must be nonzero.
+    fn range_check(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Self::Num,
+    ) -> Result<Vec<Self::Num>, Error>;
+}
+// ANCHOR_END: range-check-instructions
+
+// ANCHOR: field-config
+// The top-level config that provides all necessary columns and permutations
+// for the other configs.
+#[derive(Clone, Debug)]
+pub struct FieldConfig {
+    /// For this chip, we will use three advice columns to implement our instructions.
+    /// These are also the columns through which we communicate with other parts of
+    /// the circuit.
+    pub advice: [Column<Advice>; 3],
+
+    /// Public inputs
+    instance: Column<Instance>,
+
+    /// Constants, with equality enabled so they can be copied into advice cells.
+    constant: Column<Fixed>,
+
+    mux_config: MuxConfig,
+    n_mux_config: NMuxConfig,
+    is_zero_config: IsZeroConfig,
+    subtract_config: SubtractConfig,
+    cond_swap_config: CondSwapConfig,
+    boolean_config: BooleanConfig,
+}
+// ANCHOR END: field-config
+
+// ANCHOR: mux-config
+#[derive(Clone, Debug)]
+struct MuxConfig {
+    advice: [Column<Advice>; 3],
+    s_mux: Selector,
+}
+// ANCHOR_END: mux-config
+
+// ANCHOR: n-mux-config
+#[derive(Clone, Debug)]
+struct NMuxConfig {
+    /// The input, selector and running output columns.
+    advice: [Column<Advice>; 3],
+    /// The running sum of the selectors.
+    selector_sum: Column<Advice>,
+    s_bool: Selector,
+    s_first: Selector,
+    s_step: Selector,
+    s_last: Selector,
+}
+// ANCHOR_END: n-mux-config
+
+// ANCHOR: is-zero-config
+#[derive(Clone, Debug)]
+struct IsZeroConfig {
+    /// The input, its inverse (or zero) and the output.
+    advice: [Column<Advice>; 3],
+    s_is_zero: Selector,
+    s_zero_product: Selector,
+}
+// ANCHOR_END: is-zero-config
+
+// ANCHOR: subtract-config
+#[derive(Clone, Debug)]
+struct SubtractConfig {
+    advice: [Column<Advice>; 2],
+    s_subtract: Selector,
+}
+// ANCHOR_END: subtract-config
+
+// ANCHOR: range-check-config
+/// Configuration for checking that a value fits in `N` bits.
+#[derive(Clone, Debug)]
+struct RangeCheckConfig<const N: usize> {
+    /// Decomposes one bit per row, with the running sum alongside it.
+    decompose_config: DecomposeConfig,
+}
+// ANCHOR_END: range-check-config
+
+// ANCHOR: field-chip
+/// The top-level chip that will implement the `FieldInstructions`.
+pub struct FieldChip<F: FieldExt> {
+    config: FieldConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: field-chip
+
+// ANCHOR: mux-chip
+struct MuxChip<F: FieldExt> {
+    config: MuxConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: mux-chip
+
+// ANCHOR: n-mux-chip
+struct NMuxChip<F: FieldExt> {
+    config: NMuxConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: n-mux-chip
+
+// ANCHOR: is-zero-chip
+struct IsZeroChip<F: FieldExt> {
+    config: IsZeroConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: is-zero-chip
+
+// ANCHOR: subtract-chip
+struct SubtractChip<F: FieldExt> {
+    config: SubtractConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: subtract-chip
+
+// ANCHOR: range-check-chip
+struct RangeCheckChip<F: FieldExt, const N: usize> {
+    config: RangeCheckConfig<N>,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: range-check-chip
+
+// ANCHOR: mux-chip-trait-impl
+impl<F: FieldExt> Chip<F> for MuxChip<F> {
+    type Config = MuxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: mux-chip-trait-impl
+
+// ANCHOR: mux-chip-impl
+impl<F: FieldExt> MuxChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        let s_mux = meta.selector();
+
+        // Define our mux gate!
+        meta.create_gate("mux", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let selector = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mux = meta.query_selector(s_mux);
+
+            vec![
+                // d = (b - a) * c + a
+                s_mux.clone() * ((rhs - lhs.clone()) * selector.clone() + lhs - out),
+                // The selector must be boolean, or `d` is not one of the inputs.
+                s_mux * selector.clone() * (Expression::Constant(F::one()) - selector),
+            ]
+        });
+
+        MuxConfig { advice, s_mux }
+    }
+}
+// ANCHOR END: mux-chip-impl
+
+// ANCHOR: mux-instructions-impl
+impl<F: FieldExt> MuxInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_mux(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().mux_config.clone();
+
+        let mux_chip = MuxChip::<F>::construct(config, ());
+        mux_chip.do_mux(layouter, a, b, c)
+    }
+
+    fn do_mux_batch(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[(Self::Num, Self::Num, Self::Num)],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config().mux_config.clone();
+
+        let mux_chip = MuxChip::<F>::construct(config, ());
+        mux_chip.do_mux_batch(layouter, inputs)
+    }
+}
+
+impl<F: FieldExt> MuxInstructions<F> for MuxChip<F> {
+    type Num = Number<F>;
+
+    fn do_mux(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "mux",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single mux gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_mux.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                let selector = region.assign_advice(
+                    || "selector",
+                    config.advice[2],
+                    0,
+                    || c.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+                region.constrain_equal(c.cell, selector)?;
+
+                // Now we can assign the mux result into the output position.
+                let value = a
+                    .value
+                    .and_then(|a| b.value.and_then(|b| c.value.map(|c| (b - a) * c + a)));
+
+                let cell = region.assign_advice(
+                    || "(rhs - lhs) * selector + lhs",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                // Finally, we return a variable representing the output,
+                // to be used in another part of the circuit.
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_mux_batch(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[(Self::Num, Self::Num, Self::Num)],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+
+        let mut out = vec![];
+        layouter.assign_region(
+            || "mux batch",
+            |mut region: Region<'_, F>| {
+                out.clear();
+                // Each mux takes the same two rows as in `do_mux`, stacked one
+                // after another.
+                for (i, (a, b, c)) in inputs.iter().enumerate() {
+                    let offset = 2 * i;
+                    config.s_mux.enable(&mut region, offset)?;
+
+                    let lhs = region.assign_advice(
+                        || format!("lhs {}", i),
+                        config.advice[0],
+                        offset,
+                        || a.value.ok_or(Error::SynthesisError),
+                    )?;
+                    let rhs = region.assign_advice(
+                        || format!("rhs {}", i),
+                        config.advice[1],
+                        offset,
+                        || b.value.ok_or(Error::SynthesisError),
+                    )?;
+                    let selector = region.assign_advice(
+                        || format!("selector {}", i),
+                        config.advice[2],
+                        offset,
+                        || c.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(a.cell, lhs)?;
+                    region.constrain_equal(b.cell, rhs)?;
+                    region.constrain_equal(c.cell, selector)?;
+
+                    let value = a
+                        .value
+                        .and_then(|a| b.value.and_then(|b| c.value.map(|c| (b - a) * c + a)));
+                    let cell = region.assign_advice(
+                        || format!("(rhs - lhs) * selector + lhs {}", i),
+                        config.advice[0],
+                        offset + 1,
+                        || value.ok_or(Error::SynthesisError),
+                    )?;
+                    out.push(Number { cell, value });
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(out)
+    }
+}
+// ANCHOR END: mux-instructions-impl
+
+// ANCHOR: n-mux-chip-trait-impl
+impl<F: FieldExt> Chip<F> for NMuxChip<F> {
+    type Config = NMuxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: n-mux-chip-trait-impl
+
+// ANCHOR: n-mux-chip-impl
+impl<F: FieldExt> NMuxChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        // The running sum of the selectors needs a fourth column.
+        let selector_sum = meta.advice_column();
+        meta.enable_equality(selector_sum.into());
+
+        let s_bool = meta.selector();
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+        let s_last = meta.selector();
+
+        // Each input occupies one row, accumulating both the selected value and
+        // the number of set selectors:
+        //
+        // | a0      | a1     | a2    | a3             |
+        // |---------|--------|-------|----------------|
+        // | input_0 | sel_0  | out_0 | sel_sum_0      |
+        // | input_1 | sel_1  | out_1 | sel_sum_1      |
+        // | ...     | ...    | ...   | ...            |
+        meta.create_gate("n-mux bool", |meta| {
+            let sel = meta.query_advice(advice[1], Rotation::cur());
+            let s_bool = meta.query_selector(s_bool);
+
+            vec![s_bool * sel.clone() * (Expression::Constant(F::one()) - sel)]
+        });
+
+        meta.create_gate("n-mux first", |meta| {
+            let input = meta.query_advice(advice[0], Rotation::cur());
+            let sel = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            let sel_sum = meta.query_advice(selector_sum, Rotation::cur());
+            let s_first = meta.query_selector(s_first);
+
+            vec![
+                s_first.clone() * (sel.clone() * input - out),
+                s_first * (sel - sel_sum),
+            ]
+        });
+
+        meta.create_gate("n-mux step", |meta| {
+            let input = meta.query_advice(advice[0], Rotation::cur());
+            let sel = meta.query_advice(advice[1], Rotation::cur());
+            let out_prev = meta.query_advice(advice[2], Rotation::prev());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            let sel_sum_prev = meta.query_advice(selector_sum, Rotation::prev());
+            let sel_sum = meta.query_advice(selector_sum, Rotation::cur());
+            let s_step = meta.query_selector(s_step);
+
+            vec![
+                s_step.clone() * (out_prev + sel.clone() * input - out),
+                s_step * (sel_sum_prev + sel - sel_sum),
+            ]
+        });
+
+        // Exactly one selector is set once all of them are summed.
+        meta.create_gate("n-mux one-hot", |meta| {
+            let sel_sum = meta.query_advice(selector_sum, Rotation::cur());
+            let s_last = meta.query_selector(s_last);
+
+            vec![s_last * (sel_sum - Expression::Constant(F::one()))]
+        });
+
+        NMuxConfig {
+            advice,
+            selector_sum,
+            s_bool,
+            s_first,
+            s_step,
+            s_last,
+        }
+    }
+}
+// ANCHOR END: n-mux-chip-impl
+
+// ANCHOR: n-mux-instructions-impl
+impl<F: FieldExt> NMuxInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_mux_n(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+        selectors: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().n_mux_config.clone();
+
+        let n_mux_chip = NMuxChip::<F>::construct(config, ());
+        n_mux_chip.do_mux_n(layouter, inputs, selectors)
+    }
+}
+
+impl<F: FieldExt> NMuxInstructions<F> for NMuxChip<F> {
+    type Num = Number<F>;
+
+    fn do_mux_n(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+        selectors: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        if inputs.is_empty() || inputs.len() != selectors.len() {
+            return Err(Error::SynthesisError);
+        }
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "n-mux",
+            |mut region: Region<'_, F>| {
+                let mut acc = Some(F::zero());
+                let mut sel_acc = Some(F::zero());
+                let mut out_cell = None;
+                for (row, (input, sel)) in inputs.iter().zip(selectors.iter()).enumerate() {
+                    config.s_bool.enable(&mut region, row)?;
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_step.enable(&mut region, row)?;
+                    }
+
+                    let input_cell = region.assign_advice(
+                        || format!("input {}", row),
+                        config.advice[0],
+                        row,
+                        || input.value.ok_or(Error::SynthesisError),
+                    )?;
+                    let sel_cell = region.assign_advice(
+                        || format!("selector {}", row),
+                        config.advice[1],
+                        row,
+                        || sel.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(input.cell, input_cell)?;
+                    region.constrain_equal(sel.cell, sel_cell)?;
+
+                    acc = acc.and_then(|acc| {
+                        input
+                            .value
+                            .and_then(|input| sel.value.map(|sel| acc + sel * input))
+                    });
+                    sel_acc = sel_acc.and_then(|sel_acc| sel.value.map(|sel| sel_acc + sel));
+
+                    out_cell = Some(region.assign_advice(
+                        || format!("out {}", row),
+                        config.advice[2],
+                        row,
+                        || acc.ok_or(Error::SynthesisError),
+                    )?);
+                    region.assign_advice(
+                        || format!("selector sum {}", row),
+                        config.selector_sum,
+                        row,
+                        || sel_acc.ok_or(Error::SynthesisError),
+                    )?;
+                }
+                config.s_last.enable(&mut region, inputs.len() - 1)?;
+
+                out = Some(Number {
+                    cell: out_cell.unwrap(),
+                    value: acc,
+                });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR END: n-mux-instructions-impl
+
+// ANCHOR: is-zero-chip-trait-impl
+impl<F: FieldExt> Chip<F> for IsZeroChip<F> {
+    type Config = IsZeroConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: is-zero-chip-trait-impl
+
+// ANCHOR: is-zero-chip-impl
+impl<F: FieldExt> IsZeroChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> <Self as Chip<F>>::Config {
+        let s_is_zero = meta.selector();
+        let s_zero_product = meta.selector();
+
+        // | a0 | a1  | a2  |
+        // |----|-----|-----|
+        // | x  | inv | out |
+        //
+        // If `x != 0`, the prover must set `inv = 1/x` for the product gate to
+        // hold, forcing `out = 0`. If `x = 0`, the first gate forces `out = 1`
+        // regardless of `inv`.
+        meta.create_gate("is_zero", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let inv = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            let s_is_zero = meta.query_selector(s_is_zero);
+
+            vec![s_is_zero * (Expression::Constant(F::one()) - x * inv - out)]
+        });
+
+        meta.create_gate("is_zero product", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            let s_zero_product = meta.query_selector(s_zero_product);
+
+            vec![s_zero_product * x * out]
+        });
+
+        IsZeroConfig {
+            advice,
+            s_is_zero,
+            s_zero_product,
+        }
+    }
+}
+// ANCHOR END: is-zero-chip-impl
+
+// ANCHOR: is-zero-instructions-impl
+impl<F: FieldExt> IsZeroInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_is_zero(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config().is_zero_config.clone();
+
+        let is_zero_chip = IsZeroChip::<F>::construct(config, ());
+        is_zero_chip.do_is_zero(layouter, a)
+    }
+}
+
+impl<F: FieldExt> IsZeroInstructions<F> for IsZeroChip<F> {
+    type Num = Number<F>;
+
+    fn do_is_zero(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "is_zero",
+            |mut region: Region<'_, F>| {
+                config.s_is_zero.enable(&mut region, 0)?;
+                config.s_zero_product.enable(&mut region, 0)?;
+
+                let x = region.assign_advice(
+                    || "x",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, x)?;
+
+                // Zero has no inverse, so any value works there; we use zero.
+                let inv = a.value.map(|a| a.invert().unwrap_or(F::zero()));
+                region.assign_advice(
+                    || "inv",
+                    config.advice[1],
+                    0,
+                    || inv.ok_or(Error::SynthesisError),
+                )?;
+
+                let value = a.value.and_then(|a| inv.map(|inv| F::one() - a * inv));
+                let cell = region.assign_advice(
+                    || "1 - x * inv",
+                    config.advice[2],
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR END: is-zero-instructions-impl
+
+// ANCHOR: subtract-chip-trait-impl
+impl<F: FieldExt> Chip<F> for SubtractChip<F> {
+    type Config = SubtractConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: subtract-chip-trait-impl
+
+// ANCHOR: subtract-chip-impl
+impl<F: FieldExt> SubtractChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_subtract = meta.selector();
+
+        // Define our subtraction gate!
+        meta.create_gate("subtract", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_subtract = meta.query_selector(s_subtract);
+
+            vec![s_subtract * (lhs - rhs - out)]
+        });
+
+        SubtractConfig { advice, s_subtract }
+    }
+}
+// ANCHOR END: subtract-chip-impl
+
+// ANCHOR: subtract-instructions-impl
+impl<F: FieldExt> SubtractInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_subtract(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().subtract_config.clone();
+
+        let subtract_chip = SubtractChip::<F>::construct(config, ());
+        subtract_chip.do_subtract(layouter, a, b)
+    }
+}
+
+impl<F: FieldExt> SubtractInstructions<F> for SubtractChip<F> {
+    type Num = Number<F>;
+
+    fn do_subtract(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "subtract",
+            |mut region: Region<'_, F>| {
+                config.s_subtract.enable(&mut region, 0)?;
+
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+
+                let value = a.value.and_then(|a| b.value.map(|b| a - b));
+                let cell = region.assign_advice(
+                    || "lhs - rhs",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR END: subtract-instructions-impl
+
+// ANCHOR: range-check-chip-trait-impl
+impl<F: FieldExt, const N: usize> Chip<F> for RangeCheckChip<F, N> {
+    type Config = RangeCheckConfig<N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: range-check-chip-trait-impl
+
+// ANCHOR: range-check-chip-impl
+impl<F: FieldExt, const N: usize> RangeCheckChip<F, N> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the range check over a bit column and a running sum column.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        // A range check is a decomposition into `N` bits, one per row.
+        RangeCheckConfig {
+            decompose_config: DecomposeChip::configure(meta, &advice[..1], advice[1]),
+        }
+    }
+}
+// ANCHOR END: range-check-chip-impl
+
+// ANCHOR: range-check-instructions-impl
+impl<F: FieldExt, const N: usize> RangeCheckInstructions<F> for RangeCheckChip<F, N> {
+    type Num = Number<F>;
+
+    fn range_check(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Self::Num,
+    ) -> Result<Vec<Self::Num>, Error> {
+        // Only zero fits in 0 bits, and there are no bits to recompose it from.
+        if N == 0 {
+            return Err(Error::SynthesisError);
+        }
+
+        let decompose_chip =
+            DecomposeChip::<F>::construct(self.config().decompose_config.clone(), ());
+        decompose_chip.decompose(layouter, value, N)
+    }
+}
+// ANCHOR END: range-check-instructions-impl
+
+// ANCHOR: cond-swap-instructions-impl
+impl<F: FieldExt> CondSwapInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn conditional_swap(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        swap: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config().cond_swap_config.clone();
+
+        let cond_swap_chip = CondSwapChip::<F>::construct(config, ());
+        cond_swap_chip.conditional_swap(layouter, a, b, swap)
+    }
+}
+// ANCHOR END: cond-swap-instructions-impl
+
+// ANCHOR: boolean-instructions-impl
+impl<F: FieldExt> BooleanInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().boolean_config.clone();
+
+        let boolean_chip = BooleanChip::<F>::construct(config, ());
+        boolean_chip.and(layouter, a, b)
+    }
+
+    fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().boolean_config.clone();
+
+        let boolean_chip = BooleanChip::<F>::construct(config, ());
+        boolean_chip.or(layouter, a, b)
+    }
+
+    fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().boolean_config.clone();
+
+        let boolean_chip = BooleanChip::<F>::construct(config, ());
+        boolean_chip.xor(layouter, a, b)
+    }
+
+    fn not(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config().boolean_config.clone();
+
+        let boolean_chip = BooleanChip::<F>::construct(config, ());
+        boolean_chip.not(layouter, a)
+    }
+}
+// ANCHOR_END: boolean-instructions-impl
+
+// ANCHOR: equality-instructions-impl
+impl<F: FieldExt> EqualityInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn equals(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: <Self as EqualityInstructions<F>>::Num,
+        b: <Self as EqualityInstructions<F>>::Num,
+    ) -> Result<<Self as EqualityInstructions<F>>::Num, Error> {
+        let difference = self.do_subtract(layouter.namespace(|| "a - b"), a, b)?;
+        self.do_is_zero(layouter.namespace(|| "a - b == 0"), difference)
+    }
+}
+// ANCHOR END: equality-instructions-impl
+
+// ANCHOR: field-chip-trait-impl
+impl<F: FieldExt> Chip<F> for FieldChip<F> {
+    type Config = FieldConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: field-chip-trait-impl
+
+// ANCHOR: field-chip-impl
+impl<F: FieldExt> FieldChip<F> {
+    pub fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        let mux_config = MuxChip::configure(meta, advice);
+        let n_mux_config = NMuxChip::configure(meta, advice);
+        let is_zero_config = IsZeroChip::configure(meta, advice);
+        let subtract_config = SubtractChip::configure(meta, [advice[0], advice[1]]);
+        let cond_swap_config = CondSwapChip::configure(meta, advice);
+        let boolean_config = BooleanChip::configure(meta, advice);
+
+        meta.enable_equality(instance.into());
+        for column in &advice {
+            meta.enable_equality((*column).into());
+        }
+        // Cells in the constant column can be copied into advice cells.
+        meta.enable_constant(constant);
+
+        FieldConfig {
+            advice,
+            instance,
+            constant,
+            mux_config,
+            n_mux_config,
+            is_zero_config,
+            subtract_config,
+            cond_swap_config,
+            boolean_config,
+        }
+    }
+}
+// ANCHOR_END: field-chip-impl
+
+// ANCHOR: field-instructions-impl
+impl<F: FieldExt> FieldInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        column: Column<Advice>,
+        value: Option<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let mut num = None;
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                let cell = region.assign_advice(
+                    || "private input",
+                    column,
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+                num = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+        Ok(num.unwrap())
+    }
+
+    fn load_private_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        column: Column<Advice>,
+        values: &[Option<F>],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error> {
+        let mut nums = vec![];
+        layouter.assign_region(
+            || "load private many",
+            |mut region| {
+                nums.clear();
+                for (row, value) in values.iter().enumerate() {
+                    let cell = region.assign_advice(
+                        || format!("private input {}", row),
+                        column,
+                        row,
+                        || value.ok_or(Error::SynthesisError),
+                    )?;
+                    nums.push(Number {
+                        cell,
+                        value: *value,
+                    });
+                }
+                Ok(())
+            },
+        )?;
+        Ok(nums)
+    }
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        column: Column<Fixed>,
+        constant: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let mut num = None;
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                let cell = region.assign_fixed(|| "constant", column, 0, || Ok(constant))?;
+                num = Some(Number {
+                    cell,
+                    value: Some(constant),
+                });
+                Ok(())
+            },
+        )?;
+        Ok(num.unwrap())
+    }
+
+    /// Returns `d = (b - a) * c + a`
+    fn mux(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        c: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_mux(layouter.namespace(|| "(b - a) * c + a"), a, b, c)
+    }
+
+    fn select(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cond: <Self as FieldInstructions<F>>::Num,
+        if_true: <Self as FieldInstructions<F>>::Num,
+        if_false: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, MuxError> {
+        if let Some(value) = cond.value {
+            if value != F::zero() && value != F::one() {
+                return Err(MuxError::NonBooleanCondition);
+            }
+        }
+
+        // The mux gate itself constrains `cond` to be boolean.
+        Ok(self.do_mux(
+            layouter.namespace(|| "cond ? if_true : if_false"),
+            if_false,
+            if_true,
+            cond,
+        )?)
+    }
+
+    fn mux_batch(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[(
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+        )],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error> {
+        self.do_mux_batch(layouter.namespace(|| "mux batch"), inputs)
+    }
+
+    fn mux_n(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+        selectors: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_mux_n(
+            layouter.namespace(|| "sum(sel_i * input_i)"),
+            inputs,
+            selectors,
+        )
+    }
+
+    fn audited_branch(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cond: <Self as FieldInstructions<F>>::Num,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        audit_row: usize,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        // The mux picks its second input when the selector is 1.
+        let out = self.mux(layouter, b, a, cond.clone())?;
+        self.expose_public(layouter.namespace(|| "expose branch"), cond, audit_row)?;
+        Ok(out)
+    }
+
+    fn find_first_matching(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+        target: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<
+        (
+            <Self as FieldInstructions<F>>::Num,
+            <Self as FieldInstructions<F>>::Num,
+        ),
+        Error,
+    > {
+        let constant = self.config().constant;
+        let one = self.load_constant(layouter.namespace(|| "one"), constant, F::one())?;
+
+        let mut found = self.load_constant(layouter.namespace(|| "found"), constant, F::zero())?;
+        let mut index = self.load_constant(layouter.namespace(|| "index"), constant, F::zero())?;
+        for (i, value) in values.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("element {}", i));
+            let eq = self.equals(
+                layouter.namespace(|| "value == target"),
+                value.clone(),
+                target.clone(),
+            )?;
+            let position =
+                self.load_constant(layouter.namespace(|| "i"), constant, F::from_u64(i as u64))?;
+
+            // Once an earlier element has matched, both outputs are latched.
+            let candidate = self.mux(&mut layouter, index.clone(), position, eq.clone())?;
+            index = self.mux(&mut layouter, candidate, index, found.clone())?;
+            found = self.mux(&mut layouter, eq, one.clone(), found)?;
+        }
+        Ok((found, index))
+    }
+
+    fn update_register(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        registers: &[<Self as FieldInstructions<F>>::Num],
+        write_addr_bits: &[<Self as FieldInstructions<F>>::Num],
+        write_enable: <Self as FieldInstructions<F>>::Num,
+        value: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error> {
+        // With at least `usize::BITS` address bits, every register index is
+        // addressable.
+        let capacity = u32::try_from(write_addr_bits.len())
+            .ok()
+            .and_then(|n| 1usize.checked_shl(n));
+        if matches!(capacity, Some(capacity) if registers.len() > capacity) {
+            return Err(Error::SynthesisError);
+        }
+
+        let not_bits = write_addr_bits
+            .iter()
+            .enumerate()
+            .map(|(j, bit)| self.not(layouter.namespace(|| format!("not bit {}", j)), bit.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut out = vec![];
+        for (i, register) in registers.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("register {}", i));
+
+            // The register is written iff `write_enable` is set and every address
+            // bit matches the corresponding bit of `i`.
+            let mut write = write_enable.clone();
+            for (j, (bit, not_bit)) in write_addr_bits.iter().zip(&not_bits).enumerate() {
+                let i_bit = u32::try_from(j)
+                    .ok()
+                    .and_then(|j| i.checked_shr(j))
+                    .unwrap_or(0)
+                    & 1;
+                let matches = if i_bit == 1 { bit } else { not_bit };
+                write = self.and(
+                    layouter.namespace(|| format!("match bit {}", j)),
+                    write,
+                    matches.clone(),
+                )?;
+            }
+
+            out.push(self.mux(&mut layouter, register.clone(), value.clone(), write)?);
+        }
+        Ok(out)
+    }
+
+    fn is_zero(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_is_zero(layouter.namespace(|| "a == 0"), a)
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.constrain_instance(num.cell, config.instance, row)
+    }
+}
+// ANCHOR_END: field-instructions-impl
+
+// ANCHOR: circuit
+/// The full circuit implementation.
+///
+/// In this struct we store the private input variables. We use `Option<F>` because
+/// they won't have any value during key generation. During proving, if any of these
+/// were `None` we would get an error.
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+    pub c: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    // Since we are using a single chip for everything, we can just reuse its config.
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        // We create the two advice columns that FieldChip uses for I/O.
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+
+        // We also need an instance column to store public inputs.
+        let instance = meta.instance_column();
+
+        // And a fixed column to load constants from.
+        let constant = meta.fixed_column();
+
+        FieldChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        // Load our private values into the circuit, each in the column the mux
+        // gate reads it from.
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+        let c = field_chip.load_private(
+            layouter.namespace(|| "witness selector"),
+            advice[2],
+            self.c,
+        )?;
+
+        // Use `mux` to get `d = (b - a) * c + a
+        let d = field_chip.mux(&mut layouter, a, b, c)?;
+
+        // Expose the result as a public input to the circuit.
+        field_chip.expose_public(layouter.namespace(|| "expose d"), d, 0)
+    }
+}
+// ANCHOR_END: circuit
+
+// ANCHOR: select-circuit
+/// A circuit exposing `cond ? if_true : if_false` of its private inputs.
+#[derive(Default)]
+pub struct SelectCircuit<F: FieldExt> {
+    pub cond: Option<F>,
+    pub if_true: Option<F>,
+    pub if_false: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for SelectCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let if_true = field_chip.load_private(
+            layouter.namespace(|| "witness if_true"),
+            advice[0],
+            self.if_true,
+        )?;
+        let if_false = field_chip.load_private(
+            layouter.namespace(|| "witness if_false"),
+            advice[1],
+            self.if_false,
+        )?;
+        let cond =
+            field_chip.load_private(layouter.namespace(|| "witness cond"), advice[2], self.cond)?;
+
+        let out = field_chip.select(&mut layouter, cond, if_true, if_false)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: select-circuit
+
+// ANCHOR: constant-selector-circuit
+/// A circuit muxing its private inputs with the constant selector 1.
+#[derive(Default)]
+pub struct ConstantSelectorCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for ConstantSelectorCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let constant = config.constant;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+        let one = field_chip.load_constant(layouter.namespace(|| "one"), constant, F::one())?;
+
+        let d = field_chip.mux(&mut layouter, a, b, one)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose d"), d, 0)
+    }
+}
+// ANCHOR_END: constant-selector-circuit
+
+// ANCHOR: mux-batch-circuit
+/// A circuit exposing the mux of each private `(a, b, c)` triple.
+#[derive(Default)]
+pub struct MuxBatchCircuit<F: FieldExt> {
+    pub triples: Vec<(Option<F>, Option<F>, Option<F>)>,
+}
+
+impl<F: FieldExt> Circuit<F> for MuxBatchCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            triples: vec![(None, None, None); self.triples.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut inputs = vec![];
+        for (i, (a, b, c)) in self.triples.iter().enumerate() {
+            let mut load = |name: &str, column, value| {
+                field_chip.load_private(
+                    layouter.namespace(|| format!("witness {} {}", name, i)),
+                    column,
+                    value,
+                )
+            };
+            inputs.push((
+                load("a", advice[0], *a)?,
+                load("b", advice[1], *b)?,
+                load("c", advice[2], *c)?,
+            ));
+        }
+
+        let outputs = field_chip.mux_batch(&mut layouter, &inputs)?;
+
+        for (row, out) in outputs.into_iter().enumerate() {
+            field_chip.expose_public(
+                layouter.namespace(|| format!("expose out {}", row)),
+                out,
+                row,
+            )?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: mux-batch-circuit
+
+// ANCHOR: load-many-circuit
+/// A circuit loading all of its private inputs in one region, exposing each of
+/// them and the mux of the first two selected by the third.
+#[derive(Default)]
+pub struct LoadManyCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for LoadManyCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = field_chip.load_private_many(
+            layouter.namespace(|| "witness values"),
+            advice[0],
+            &self.values,
+        )?;
+        if values.len() < 3 {
+            return Err(Error::SynthesisError);
+        }
+
+        let d = field_chip.mux(
+            &mut layouter,
+            values[0].clone(),
+            values[1].clone(),
+            values[2].clone(),
+        )?;
+
+        let rows = values.len();
+        for (row, value) in values.into_iter().enumerate() {
+            field_chip.expose_public(
+                layouter.namespace(|| format!("expose value {}", row)),
+                value,
+                row,
+            )?;
+        }
+        field_chip.expose_public(layouter.namespace(|| "expose d"), d, rows)
+    }
+}
+// ANCHOR_END: load-many-circuit
+
+// ANCHOR: const-cell-circuit
+/// A circuit exposing the advice cells built by `const_cell` and `zero_cell`.
+#[derive(Default)]
+pub struct ConstCellCircuit<F: FieldExt> {
+    pub value: F,
+}
+
+impl<F: FieldExt> Circuit<F> for ConstCellCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { value: self.value }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let value = const_cell(layouter.namespace(|| "const"), advice[0], self.value)?;
+        let zero = zero_cell(layouter.namespace(|| "zero"), advice[1])?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose const"), value, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose zero"), zero, 1)
+    }
+}
+// ANCHOR_END: const-cell-circuit
+
+// ANCHOR: load-bytes-circuit
+/// A circuit exposing the field element decoded from its 32-byte encoding.
+#[derive(Default)]
+pub struct LoadBytesCircuit {
+    pub bytes: [u8; 32],
+}
+
+impl<F: FieldExt> Circuit<F> for LoadBytesCircuit {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { bytes: self.bytes }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let value =
+            load_private_from_bytes(layouter.namespace(|| "load bytes"), advice[0], &self.bytes)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose value"), value, 0)
+    }
+}
+// ANCHOR_END: load-bytes-circuit
+
+// ANCHOR: layout-circuit
+/// A circuit exposing its private input, loaded with `assign_or_default` so it
+/// can be synthesized without witnesses.
+#[derive(Default)]
+pub struct LayoutCircuit<F: FieldExt> {
+    pub a: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for LayoutCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut a = None;
+        layouter.assign_region(
+            || "load a",
+            |mut region| {
+                a = Some(assign_or_default(
+                    &mut region,
+                    || "a",
+                    advice[0],
+                    0,
+                    self.a,
+                )?);
+                Ok(())
+            },
+        )?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose a"), a.unwrap(), 0)
+    }
+}
+// ANCHOR_END: layout-circuit
+
+// ANCHOR: n-mux-circuit
+/// A circuit exposing the input picked out by a private one-hot selector.
+#[derive(Default)]
+pub struct NMuxCircuit<F: FieldExt> {
+    pub inputs: Vec<Option<F>>,
+    pub selectors: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for NMuxCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            inputs: vec![None; self.inputs.len()],
+            selectors: vec![None; self.selectors.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let inputs = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(
+                    layouter.namespace(|| format!("witness input {}", i)),
+                    advice[0],
+                    *value,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let selectors = self
+            .selectors
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(
+                    layouter.namespace(|| format!("witness selector {}", i)),
+                    advice[1],
+                    *value,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let out = field_chip.mux_n(&mut layouter, &inputs, &selectors)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: n-mux-circuit
+
+// ANCHOR: is-zero-circuit
+/// A circuit exposing whether its private input is zero.
+#[derive(Default)]
+pub struct IsZeroCircuit<F: FieldExt> {
+    pub a: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for IsZeroCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+
+        let out = field_chip.is_zero(&mut layouter, a)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: is-zero-circuit
+
+// ANCHOR: equals-circuit
+/// A circuit exposing whether `a == b`, and `y` if so or `x` otherwise.
+#[derive(Default)]
+pub struct EqualsCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+    pub x: Option<F>,
+    pub y: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for EqualsCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+        let x = field_chip.load_private(layouter.namespace(|| "witness x"), advice[0], self.x)?;
+        let y = field_chip.load_private(layouter.namespace(|| "witness y"), advice[1], self.y)?;
+
+        let eq = field_chip.equals(layouter.namespace(|| "a == b"), a, b)?;
+        let out = field_chip.mux(&mut layouter, x, y, eq.clone())?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose eq"), eq, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 1)
+    }
+}
+// ANCHOR_END: equals-circuit
+
+// ANCHOR: audited-branch-circuit
+/// A circuit exposing `cond ? a : b` in row 0, and the branch taken in row 1.
+#[derive(Default)]
+pub struct AuditedBranchCircuit<F: FieldExt> {
+    pub cond: Option<F>,
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for AuditedBranchCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+        let cond =
+            field_chip.load_private(layouter.namespace(|| "witness cond"), advice[2], self.cond)?;
+
+        let out = field_chip.audited_branch(&mut layouter, cond, a, b, 1)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: audited-branch-circuit
+
+// ANCHOR: find-first-circuit
+/// A circuit exposing whether `target` occurs in `values`, and the index of its
+/// first occurrence.
+#[derive(Default)]
+pub struct FindFirstCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+    pub target: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for FindFirstCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+            target: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(
+                    layouter.namespace(|| format!("witness value {}", i)),
+                    advice[0],
+                    *value,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let target = field_chip.load_private(
+            layouter.namespace(|| "witness target"),
+            advice[1],
+            self.target,
+        )?;
+
+        let (found, index) = field_chip.find_first_matching(&mut layouter, &values, target)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose found"), found, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose index"), index, 1)
+    }
+}
+// ANCHOR_END: find-first-circuit
+
+// ANCHOR: register-circuit
+/// A circuit exposing a register bank after a conditional write of `value` to
+/// the register at `write_addr_bits`.
+#[derive(Default)]
+pub struct RegisterCircuit<F: FieldExt> {
+    pub registers: Vec<Option<F>>,
+    pub write_addr_bits: Vec<Option<F>>,
+    pub write_enable: Option<F>,
+    pub value: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for RegisterCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            registers: vec![None; self.registers.len()],
+            write_addr_bits: vec![None; self.write_addr_bits.len()],
+            write_enable: None,
+            value: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut load = |name: &str, values: &[Option<F>]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    field_chip.load_private(
+                        layouter.namespace(|| format!("witness {} {}", name, i)),
+                        advice[0],
+                        *value,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let registers = load("register", &self.registers)?;
+        let write_addr_bits = load("address bit", &self.write_addr_bits)?;
+
+        let write_enable = field_chip.load_private(
+            layouter.namespace(|| "witness write enable"),
+            advice[1],
+            self.write_enable,
+        )?;
+        let value = field_chip.load_private(
+            layouter.namespace(|| "witness value"),
+            advice[2],
+            self.value,
+        )?;
+
+        let registers = field_chip.update_register(
+            &mut layouter,
+            &registers,
+            &write_addr_bits,
+            write_enable,
+            value,
+        )?;
+
+        for (row, register) in registers.into_iter().enumerate() {
+            field_chip.expose_public(
+                layouter.namespace(|| format!("expose register {}", row)),
+                register,
+                row,
+            )?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: register-circuit
+
+// ANCHOR: cond-swap-circuit
+/// A circuit exposing `(a, b)`, swapped if the private flag `swap` is set.
+#[derive(Default)]
+pub struct CondSwapCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+    pub swap: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for CondSwapCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+        let swap =
+            field_chip.load_private(layouter.namespace(|| "witness swap"), advice[2], self.swap)?;
+
+        let (out0, out1) =
+            field_chip.conditional_swap(layouter.namespace(|| "cond swap"), a, b, swap)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out0"), out0, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose out1"), out1, 1)
+    }
+}
+// ANCHOR_END: cond-swap-circuit
+
+// ANCHOR: boolean-circuit
+/// A circuit exposing `a AND b`, `a OR b`, `a XOR b` and `NOT a`.
+#[derive(Default)]
+pub struct BooleanCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for BooleanCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+
+        let and = field_chip.and(layouter.namespace(|| "a and b"), a.clone(), b.clone())?;
+        let or = field_chip.or(layouter.namespace(|| "a or b"), a.clone(), b.clone())?;
+        let xor = field_chip.xor(layouter.namespace(|| "a xor b"), a.clone(), b)?;
+        let not = field_chip.not(layouter.namespace(|| "not a"), a)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose and"), and, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose or"), or, 1)?;
+        field_chip.expose_public(layouter.namespace(|| "expose xor"), xor, 2)?;
+        field_chip.expose_public(layouter.namespace(|| "expose not"), not, 3)
+    }
+}
+// ANCHOR_END: boolean-circuit
+
+// ANCHOR: range-check-circuit
+#[derive(Clone, Debug)]
+pub struct RangeCheckCircuitConfig<const N: usize> {
+    field_config: FieldConfig,
+    range_check_config: RangeCheckConfig<N>,
+}
+
+/// A circuit proving that its private input fits in `N` bits.
+#[derive(Default)]
+pub struct RangeCheckCircuit<F: FieldExt, const N: usize> {
+    pub value: Option<F>,
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for RangeCheckCircuit<F, N> {
+    type Config = RangeCheckCircuitConfig<N>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        RangeCheckCircuitConfig {
+            field_config: FieldChip::configure(meta, advice, instance, constant),
+            range_check_config: RangeCheckChip::<F, N>::configure(meta, [advice[0], advice[1]]),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.field_config.advice;
+        let field_chip = FieldChip::<F>::construct(config.field_config, ());
+        let range_check_chip = RangeCheckChip::<F, N>::construct(config.range_check_config, ());
+
+        let value = field_chip.load_private(
+            layouter.namespace(|| "witness value"),
+            advice[0],
+            self.value,
+        )?;
+
+        range_check_chip.range_check(layouter.namespace(|| "range check"), value)?;
+        Ok(())
+    }
+}
+// ANCHOR_END: range-check-circuit
+
+// ANCHOR: compare-circuit
+#[derive(Clone, Debug)]
+pub struct CompareCircuitConfig<const N: usize> {
+    field_config: FieldConfig,
+    compare_config: CompareConfig<N>,
+}
+
+/// A circuit exposing whether `a > b` for private `N`-bit inputs.
+#[derive(Default)]
+pub struct CompareCircuit<F: FieldExt, const N: usize> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for CompareCircuit<F, N> {
+    type Config = CompareCircuitConfig<N>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        CompareCircuitConfig {
+            field_config: FieldChip::configure(meta, advice, instance, constant),
+            compare_config: CompareChip::<F, N>::configure(meta, advice),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.field_config.advice;
+        let field_chip = FieldChip::<F>::construct(config.field_config, ());
+        let compare_chip = CompareChip::<F, N>::construct(config.compare_config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "witness a"), advice[0], self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "witness b"), advice[1], self.b)?;
+
+        let gt = compare_chip.greater_than(layouter.namespace(|| "a > b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose a > b"), gt, 0)
+    }
+}
+// ANCHOR_END: compare-circuit
+
+// ANCHOR: decompose-circuit
+#[derive(Clone, Debug)]
+pub struct DecomposeCircuitConfig {
+    field_config: FieldConfig,
+    decompose_config: DecomposeConfig,
+}
+
+/// A circuit exposing the `n` little-endian bits of its private input.
+#[derive(Default)]
+pub struct DecomposeCircuit<F: FieldExt> {
+    pub value: Option<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> Circuit<F> for DecomposeCircuit<F> {
+    type Config = DecomposeCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            value: None,
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        // Two bits per row, with the running sum in the third column.
+        DecomposeCircuitConfig {
+            field_config: FieldChip::configure(meta, advice, instance, constant),
+            decompose_config: DecomposeChip::configure(meta, &advice[..2], advice[2]),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.field_config.advice;
+        let field_chip = FieldChip::<F>::construct(config.field_config, ());
+        let decompose_chip = DecomposeChip::<F>::construct(config.decompose_config, ());
+
+        let value = field_chip.load_private(
+            layouter.namespace(|| "witness value"),
+            advice[0],
+            self.value,
+        )?;
+
+        let bits = decompose_chip.decompose(layouter.namespace(|| "decompose"), value, self.n)?;
+
+        for (row, bit) in bits.into_iter().enumerate() {
+            field_chip.expose_public(
+                layouter.namespace(|| format!("expose bit {}", row)),
+                bit,
+                row,
+            )?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: decompose-circuit
+
+// ANCHOR: prove-and-verify
+/// Generates the parameters and proving key for `circuit` over the IPA
+/// commitment scheme, with `2^k` rows.
+pub fn keygen<C: Circuit<pasta::Fp>>(
+    k: u32,
+    circuit: &C,
+) -> Result<(Params<EqAffine>, ProvingKey<EqAffine>), Error> {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+    Ok((params, pk))
+}
+
+/// Creates a proof that `circuit` is satisfied with `public_inputs`.
+pub fn prove<C: Circuit<pasta::Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &C,
+    public_inputs: &[pasta::Fp],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        std::slice::from_ref(circuit),
+        &[&[public_inputs]],
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Returns whether `proof` verifies against `public_inputs`.
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public_inputs: &[pasta::Fp],
+) -> bool {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[&[public_inputs]], &mut transcript).is_ok()
+}
+
+/// Runs the full proving pipeline for `circuit` over the IPA commitment scheme:
+/// key generation, proof creation and verification against `public_inputs`.
+///
+/// Returns `Ok(false)` if the proof does not verify, and an error if keygen or
+/// proving fails.
+pub fn prove_and_verify<C: Circuit<pasta::Fp>>(
+    k: u32,
+    circuit: C,
+    public_inputs: &[pasta::Fp],
+) -> Result<bool, Error> {
+    let (params, pk) = keygen(k, &circuit)?;
+    let proof = prove(&params, &pk, &circuit, public_inputs)?;
+    Ok(verify(&params, pk.get_vk(), &proof, public_inputs))
+}
+// ANCHOR_END: prove-and-verify
+
+// ANCHOR: build-circuit
+/// Builds a batch of muxes sized to fill a fair fraction of the `2^k` rows,
+/// along with its public inputs, for timing the proving pipeline as `k` grows.
+/// Panics if `k < 4`.
+pub fn build_circuit(k: u32) -> (MuxBatchCircuit<pasta::Fp>, Vec<pasta::Fp>) {
+    assert!(k >= 4, "build_circuit requires k >= 4");
+
+    let triples: Vec<(pasta::Fp, pasta::Fp, pasta::Fp)> = (0..1u64 << (k - 4))
+        .map(|i| {
+            (
+                pasta::Fp::from_u64(2 * i),
+                pasta::Fp::from_u64(2 * i + 1),
+                pasta::Fp::from_u64(i % 2),
+            )
+        })
+        .collect();
+    let outputs = triples.iter().map(|&(a, b, c)| (b - a) * c + a).collect();
+
+    let circuit = MuxBatchCircuit {
+        triples: triples
+            .into_iter()
+            .map(|(a, b, c)| (Some(a), Some(b), Some(c)))
+            .collect(),
+    };
+    (circuit, outputs)
+}
+// ANCHOR_END: build-circuit
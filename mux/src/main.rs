@@ -1,428 +1,660 @@
-use std::marker::PhantomData;
+use std::cell::RefCell;
 
 use halo2::{
     arithmetic::FieldExt,
-    circuit::{Cell, Chip, Layouter, Region, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
-    poly::Rotation,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
 };
 
-// ANCHOR: field-instructions
-/// A variable representing a number.
-#[derive(Clone)]
-struct Number<F: FieldExt> {
-    cell: Cell,
-    value: Option<F>,
-}
-
-trait FieldInstructions<F: FieldExt>: MuxInstructions<F> {
-    /// Variable representing a number.
-    type Num;
-
-    /// Loads a number into the circuit as a private input.
-    fn load_private(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Option<F>,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
-
-    /// Returns `d = (b - a) * c + a`.
-    fn mux(
-        &self,
-        layouter: &mut impl Layouter<F>,
-        a: <Self as FieldInstructions<F>>::Num,
-        b: <Self as FieldInstructions<F>>::Num,
-        c: <Self as FieldInstructions<F>>::Num,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
-
-    /// Exposes a number as a public input to the circuit.
-    fn expose_public(
-        &self,
-        layouter: impl Layouter<F>,
-        num: <Self as FieldInstructions<F>>::Num,
-        row: usize,
-    ) -> Result<(), Error>;
-}
-// ANCHOR_END: field-instructions
-
-// ANCHOR: mux-instructions
-trait MuxInstructions<F: FieldExt>: Chip<F> {
-    /// Variable representing a number.
-    type Num;
-
-    /// Returns `c = a + b`.
-    fn do_mux(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-        c: Self::Num,
-    ) -> Result<Self::Num, Error>;
-}
-// ANCHOR_END: mux-instructions
-
-// ANCHOR: field-config
-// The top-level config that provides all necessary columns and permutations
-// for the other configs.
-#[derive(Clone, Debug)]
-struct FieldConfig {
-    /// For this chip, we will use three advice columns to implement our instructions.
-    /// These are also the columns through which we communicate with other parts of
-    /// the circuit.
-    advice: [Column<Advice>; 3],
-
-    /// Public inputs
-    instance: Column<Instance>,
-
-    mux_config: MuxConfig,
-}
-// ANCHOR END: field-config
-
-// ANCHOR: mux-config
-#[derive(Clone, Debug)]
-struct MuxConfig {
-    advice: [Column<Advice>; 3],
-    s_mux: Selector,
-}
-// ANCHOR_END: mux-config
+use mux1::{
+    build_circuit, prove_and_verify, AuditedBranchCircuit, BooleanCircuit, CompareCircuit,
+    CondSwapCircuit, ConstCellCircuit, ConstantSelectorCircuit, DecomposeCircuit, EqualsCircuit,
+    FieldChip, FieldConfig, FieldInstructions, FindFirstCircuit, IsZeroCircuit, LayoutCircuit,
+    LoadBytesCircuit, LoadManyCircuit, MuxBatchCircuit, MuxError, MyCircuit, NMuxCircuit,
+    RangeCheckCircuit, RegisterCircuit, SelectCircuit,
+};
 
-// ANCHOR: field-chip
-/// The top-level chip that will implement the `FieldInstructions`.
-struct FieldChip<F: FieldExt> {
-    config: FieldConfig,
-    _marker: PhantomData<F>,
-}
-// ANCHOR_END: field-chip
+mod test_utils;
+use test_utils::{assert_fails_gate, record_advice_columns};
 
-// ANCHOR: mux-chip
-struct MuxChip<F: FieldExt> {
-    config: MuxConfig,
-    _marker: PhantomData<F>,
+// ANCHOR: select-error-circuit
+/// Calls `select` on its private inputs and keeps the error it returns, so
+/// that the tests can tell the `MuxError` variants apart.
+#[derive(Default)]
+struct SelectErrorCircuit<F: FieldExt> {
+    cond: Option<F>,
+    if_true: Option<F>,
+    if_false: Option<F>,
+    /// Loads `if_true` into a column without equality enabled, which `select`
+    /// then fails to copy from.
+    uncopyable_if_true: bool,
+    error: RefCell<Option<MuxError>>,
 }
-// ANCHOR END: mux-chip
 
-// ANCHOR: mux-chip-trait-impl
-impl<F: FieldExt> Chip<F> for MuxChip<F> {
-    type Config = MuxConfig;
-    type Loaded = ();
-
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
-
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
-// ANCHOR END: mux-chip-trait-impl
+impl<F: FieldExt> Circuit<F> for SelectErrorCircuit<F> {
+    type Config = (FieldConfig, Column<Advice>);
+    type FloorPlanner = SimpleFloorPlanner;
 
-// ANCHOR: mux-chip-impl
-impl<F: FieldExt> MuxChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+    fn without_witnesses(&self) -> Self {
         Self {
-            config,
-            _marker: PhantomData,
+            uncopyable_if_true: self.uncopyable_if_true,
+            ..Self::default()
         }
     }
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 3],
-    ) -> <Self as Chip<F>>::Config {
-        let s_mux = meta.selector();
-
-        // Define our mux gate!
-        meta.create_gate("mux", |meta| {
-            let lhs = meta.query_advice(advice[0], Rotation::cur());
-            let rhs = meta.query_advice(advice[1], Rotation::cur());
-            let xhs = meta.query_advice(advice[2], Rotation::cur());
-            let out = meta.query_advice(advice[0], Rotation::next());
-            let s_mux = meta.query_selector(s_mux);
-
-            //d = (b - a) * c;// + a;
-            vec![s_mux * ((rhs - lhs.clone()) * xhs.clone() - out)]
-        });
-
-        MuxConfig { advice, s_mux }
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        (MyCircuit::configure(meta), meta.advice_column())
     }
-}
-// ANCHOR END: mux-chip-impl
 
-// ANCHOR: mux-instructions-impl
-impl<F: FieldExt> MuxInstructions<F> for FieldChip<F> {
-    type Num = Number<F>;
-    fn do_mux(
+    fn synthesize(
         &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-        c: Self::Num,
-    ) -> Result<Self::Num, Error> {
-        let config = self.config().mux_config.clone();
-
-        let mux_chip = MuxChip::<F>::construct(config, ());
-        mux_chip.do_mux(layouter, a, b, c)
+        (config, uncopyable): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let advice = config.advice;
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let if_true_column = if self.uncopyable_if_true {
+            uncopyable
+        } else {
+            advice[0]
+        };
+        let if_true = field_chip.load_private(
+            layouter.namespace(|| "witness if_true"),
+            if_true_column,
+            self.if_true,
+        )?;
+        let if_false = field_chip.load_private(
+            layouter.namespace(|| "witness if_false"),
+            advice[1],
+            self.if_false,
+        )?;
+        let cond =
+            field_chip.load_private(layouter.namespace(|| "witness cond"), advice[2], self.cond)?;
+
+        match field_chip.select(&mut layouter, cond, if_true, if_false) {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                *self.error.borrow_mut() = Some(error);
+                Err(Error::SynthesisError)
+            }
+        }
     }
 }
+// ANCHOR_END: select-error-circuit
 
-impl<F: FieldExt> MuxInstructions<F> for MuxChip<F> {
-    type Num = Number<F>;
+// ANCHOR: run-mux
+/// Checks the mux circuit over the field `F`.
+fn run_mux<F: FieldExt>() {
+    use halo2::dev::MockProver;
 
-    fn do_mux(
-        &self,
-        mut layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-        c: Self::Num,
-    ) -> Result<Self::Num, Error> {
-        let config = self.config();
-
-        let mut out = None;
-        layouter.assign_region(
-            || "mux",
-            |mut region: Region<'_, F>| {
-                // We only want to use a single mux gate in this region,
-                // so we enable it at region offset 0; this means it will constrain
-                // cells at offsets 0 and 1.
-                config.s_mux.enable(&mut region, 0)?;
-
-                // The inputs we've been given could be located anywhere in the circuit,
-                // but we can only rely on relative offsets inside this region. So we
-                // assign new cells inside the region and constrain them to have the
-                // same values as the inputs.
-                let lhs = region.assign_advice(
-                    || "lhs",
-                    config.advice[0],
-                    0,
-                    || a.value.ok_or(Error::SynthesisError),
-                )?;
-                let rhs = region.assign_advice(
-                    || "rhs",
-                    config.advice[1],
-                    0,
-                    || b.value.ok_or(Error::SynthesisError),
-                )?;
-                let xhs = region.assign_advice(
-                    || "xhs",
-                    config.advice[2],
-                    0,
-                    || b.value.ok_or(Error::SynthesisError),
-                )?;
-                region.constrain_equal(a.cell, lhs)?;
-                region.constrain_equal(b.cell, rhs)?;
-                region.constrain_equal(c.cell, xhs)?;
-
-                // Now we can assign the mux result into the output position.
-                let value = a.value.and_then(|a| b.value.map(|b| (b - a)));
-
-                let cell = region.assign_advice(
-                    || "lhs * rhs",
-                    config.advice[0],
-                    1,
-                    || value.ok_or(Error::SynthesisError),
-                )?;
-
-                // Finally, we return a variable representing the output,
-                // to be used in another part of the circuit.
-                out = Some(Number { cell, value });
-                Ok(())
-            },
-        )?;
+    // The number of rows in our circuit cannot exceed 2^k. Since our example
+    // circuit is very small, we can pick a very small value here.
+    let k = 4;
 
-        Ok(out.unwrap())
-    }
-}
-// ANCHOR END: mux-instructions-impl
+    // Prepare the private and public inputs to the circuit! A selector of 1
+    // picks `b`.
+    let a = F::rand();
+    let b = F::rand();
+    let c = F::one();
+    let d = (b - a) * c + a;
+
+    // Instantiate the circuit with the private inputs.
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+    };
 
-// ANCHOR: field-chip-trait-impl
-impl<F: FieldExt> Chip<F> for FieldChip<F> {
-    type Config = FieldConfig;
-    type Loaded = ();
+    // Arrange the public input. We expose the mux result in row 0
+    // of the instance column, so we position it there in our public inputs.
+    let mut public_inputs = vec![d];
 
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
+    // Given the correct public input, our circuit will verify.
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
+    // If we try some other public input, the proof will fail!
+    public_inputs[0] += F::one();
+    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    assert!(prover.verify().is_err());
 }
-// ANCHOR_END: field-chip-trait-impl
+// ANCHOR_END: run-mux
 
-// ANCHOR: field-chip-impl
-impl<F: FieldExt> FieldChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
-    }
+#[allow(clippy::many_single_char_names)]
+fn main() {
+    use halo2::{
+        dev::MockProver,
+        pasta::{Fp, Fq},
+    };
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 3],
-        instance: Column<Instance>,
-    ) -> <Self as Chip<F>>::Config {
-        let mux_config = MuxChip::configure(meta, advice);
+    // ANCHOR: test-witness-columns
+    // `a`, `b` and the selector are loaded into the columns `advice[0]`,
+    // `advice[1]` and `advice[2]` respectively, which must be distinct.
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let config = MyCircuit::<Fp>::configure(&mut meta);
+    let columns = record_advice_columns(&MyCircuit::<Fp>::default()).unwrap();
+    assert_eq!(
+        [
+            columns.in_namespace("witness a"),
+            columns.in_namespace("witness b"),
+            columns.in_namespace("witness selector"),
+        ],
+        [
+            vec![config.advice[0]],
+            vec![config.advice[1]],
+            vec![config.advice[2]],
+        ]
+    );
+    assert_ne!(config.advice[0], config.advice[1]);
+    assert_ne!(config.advice[1], config.advice[2]);
+    assert_ne!(config.advice[0], config.advice[2]);
+    // ANCHOR_END: test-witness-columns
 
-        meta.enable_equality(instance.into());
-        for column in &advice {
-            meta.enable_equality((*column).into());
-        }
+    // ANCHOR: test-circuit
+    // The mux circuit is generic over the field, so we check it over both
+    // Pasta fields.
+    run_mux::<Fp>();
+    run_mux::<Fq>();
 
-        FieldConfig {
-            advice,
-            instance,
-            mux_config,
-        }
-    }
-}
-// ANCHOR_END: field-chip-impl
+    // The number of rows in our circuit cannot exceed 2^k. Since our example
+    // circuit is very small, we can pick a very small value here.
+    let k = 4;
+    // ANCHOR_END: test-circuit
 
-// ANCHOR: field-instructions-impl
-impl<F: FieldExt> FieldInstructions<F> for FieldChip<F> {
-    type Num = Number<F>;
+    // ANCHOR: test-prove-and-verify
+    // A real proof for the same circuit verifies against the correct public
+    // input, and not against a wrong one.
+    let a = Fp::rand();
+    let b = Fp::rand();
+    let c = Fp::one();
+    let d = (b - a) * c + a;
 
-    fn load_private(
-        &self,
-        mut layouter: impl Layouter<F>,
-        value: Option<F>,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
-        let config = self.config();
-
-        let mut num = None;
-        layouter.assign_region(
-            || "load private",
-            |mut region| {
-                let cell = region.assign_advice(
-                    || "private input",
-                    config.advice[0],
-                    0,
-                    || value.ok_or(Error::SynthesisError),
-                )?;
-                num = Some(Number { cell, value });
-                Ok(())
-            },
-        )?;
-        Ok(num.unwrap())
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+    };
+    assert!(prove_and_verify(k, circuit, &[d]).unwrap());
+
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+    };
+    assert!(!prove_and_verify(k, circuit, &[d + Fp::one()]).unwrap());
+
+    let (circuit, public_inputs) = build_circuit(k);
+    assert!(prove_and_verify(k, circuit, &public_inputs).unwrap());
+    // ANCHOR_END: test-prove-and-verify
+
+    // ANCHOR: test-constant-selector
+    // A constant selector of 1 always picks `b`.
+    let a = Fp::rand();
+    let b = Fp::rand();
+    let circuit = ConstantSelectorCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![b]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(k, &circuit, vec![vec![a]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-constant-selector
+
+    // ANCHOR: test-load-many
+    // Five values loaded in one region: each is exposed as-is, and the first
+    // two are muxed by the third (1), which picks the second.
+    let values = vec![Fp::rand(), Fp::rand(), Fp::one(), Fp::rand(), Fp::rand()];
+    let circuit = LoadManyCircuit {
+        values: values.iter().copied().map(Some).collect(),
+    };
+    let mut public_inputs = values.clone();
+    public_inputs.push(values[1]);
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    for row in 0..public_inputs.len() {
+        let mut public_inputs = public_inputs.clone();
+        public_inputs[row] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
     }
+    // ANCHOR_END: test-load-many
 
-    /// Returns `d = (b - a) * c + a`
-    fn mux(
-        &self,
-        layouter: &mut impl Layouter<F>,
-        a: <Self as FieldInstructions<F>>::Num,
-        b: <Self as FieldInstructions<F>>::Num,
-        c: <Self as FieldInstructions<F>>::Num,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
-        self.do_mux(layouter.namespace(|| "(b - a) * c + a"), a, b, c)
+    // ANCHOR: test-mux-bool
+    // A selector of 2 is rejected, even with the output `(b - a) * 2 + a` that
+    // the arithmetic alone would accept.
+    let a = Fp::rand();
+    let b = Fp::rand();
+    let c = Fp::from_u64(2);
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![(b - a) * c + a]]).unwrap();
+    assert_fails_gate(&prover, "mux");
+    // ANCHOR_END: test-mux-bool
+
+    // ANCHOR: test-select
+    let if_true = Fp::rand();
+    let if_false = Fp::rand();
+    for (cond, expected) in [(Fp::one(), if_true), (Fp::zero(), if_false)] {
+        let circuit = SelectCircuit {
+            cond: Some(cond),
+            if_true: Some(if_true),
+            if_false: Some(if_false),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let unchosen = if cond == Fp::one() { if_false } else { if_true };
+        let prover = MockProver::run(k, &circuit, vec![vec![unchosen]]).unwrap();
+        assert!(prover.verify().is_err());
     }
 
-    fn expose_public(
-        &self,
-        mut layouter: impl Layouter<F>,
-        num: <Self as FieldInstructions<F>>::Num,
-        row: usize,
-    ) -> Result<(), Error> {
-        let config = self.config();
+    // A non-boolean condition is rejected by `select` itself.
+    let circuit = SelectErrorCircuit {
+        cond: Some(Fp::from_u64(2)),
+        if_true: Some(if_true),
+        if_false: Some(if_false),
+        ..Default::default()
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![]]).is_err());
+    assert!(matches!(
+        circuit.error.take(),
+        Some(MuxError::NonBooleanCondition)
+    ));
+
+    // Failing to lay out the mux is any other synthesis error.
+    let circuit = SelectErrorCircuit {
+        cond: Some(Fp::one()),
+        if_true: Some(if_true),
+        if_false: Some(if_false),
+        uncopyable_if_true: true,
+        ..Default::default()
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![]]).is_err());
+    assert!(matches!(circuit.error.take(), Some(MuxError::Synthesis(_))));
+
+    // A missing witness fails while loading it, before `select` is reached.
+    let circuit = SelectCircuit {
+        cond: Some(Fp::one()),
+        if_true: None,
+        if_false: Some(if_false),
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![if_true]]).is_err());
+    // ANCHOR_END: test-select
+
+    // ANCHOR: test-mux-batch
+    // Four muxes in one region, with selectors 0, 1, 1, 0.
+    let triples: Vec<(Fp, Fp, Fp)> = [0, 1, 1, 0]
+        .iter()
+        .map(|&c| (Fp::rand(), Fp::rand(), Fp::from_u64(c)))
+        .collect();
+    let circuit = MuxBatchCircuit {
+        triples: triples
+            .iter()
+            .map(|&(a, b, c)| (Some(a), Some(b), Some(c)))
+            .collect(),
+    };
+    let public_inputs: Vec<Fp> = triples.iter().map(|&(a, b, c)| (b - a) * c + a).collect();
+    let prover = MockProver::run(5, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-        layouter.constrain_instance(num.cell, config.instance, row)
+    // Exposing the unselected input of any triple fails.
+    for (row, &(a, b, c)) in triples.iter().enumerate() {
+        let mut public_inputs = public_inputs.clone();
+        public_inputs[row] = (a - b) * c + b;
+        let prover = MockProver::run(5, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
     }
-}
-// ANCHOR_END: field-instructions-impl
-
-// ANCHOR: circuit
-/// The full circuit implementation.
-///
-/// In this struct we store the private input variables. We use `Option<F>` because
-/// they won't have any value during key generation. During proving, if any of these
-/// were `None` we would get an error.
-#[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    a: Option<F>,
-    b: Option<F>,
-    c: Option<F>,
-}
+    // ANCHOR_END: test-mux-batch
 
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-    // Since we are using a single chip for everything, we can just reuse its config.
-    type Config = FieldConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+    // ANCHOR: test-const-cell
+    let circuit = ConstCellCircuit {
+        value: Fp::from_u64(7),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from_u64(7), Fp::zero()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from_u64(7), Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-const-cell
+
+    // ANCHOR: test-load-bytes
+    // The little-endian encoding of 0x0102 loads as that field element.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 0x02;
+    bytes[1] = 0x01;
+    let circuit = LoadBytesCircuit { bytes };
+    let prover = MockProver::<Fp>::run(k, &circuit, vec![vec![Fp::from_u64(0x0102)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        // We create the two advice columns that FieldChip uses for I/O.
-        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let prover = MockProver::<Fp>::run(k, &circuit, vec![vec![Fp::from_u64(0x0201)]]).unwrap();
+    assert!(prover.verify().is_err());
 
-        // We also need an instance column to store public inputs.
-        let instance = meta.instance_column();
+    // All-ones bytes exceed the modulus, so synthesis is rejected outright.
+    let circuit = LoadBytesCircuit { bytes: [0xff; 32] };
+    assert!(MockProver::<Fp>::run(k, &circuit, vec![vec![Fp::zero()]]).is_err());
+    // ANCHOR_END: test-load-bytes
 
-        FieldChip::configure(meta, advice, instance)
-    }
+    // ANCHOR: test-assign-or-default
+    let a = Fp::rand();
+    let circuit = LayoutCircuit { a: Some(a) };
+    let prover = MockProver::run(k, &circuit, vec![vec![a]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        let field_chip = FieldChip::<F>::construct(config, ());
+    // Without witnesses the circuit still lays out, with `a` assigned as zero.
+    // This is only useful for inspecting the layout: the zero is not a proof
+    // of anything about the real input.
+    let prover = MockProver::run(k, &circuit.without_witnesses(), vec![vec![Fp::zero()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    // ANCHOR_END: test-assign-or-default
 
-        // Load our private values into the circuit.
-        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
-        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
-        let c = field_chip.load_private(layouter.namespace(|| "load c"), self.c)?;
+    // ANCHOR: test-n-mux
+    // Selecting index 2 out of 4 inputs exposes `inputs[2]`.
+    let inputs: Vec<Fp> = (0..4).map(|_| Fp::rand()).collect();
+    let one_hot = |bits: [u64; 4]| -> Vec<Option<Fp>> {
+        bits.iter().map(|&bit| Some(Fp::from_u64(bit))).collect()
+    };
+    let circuit = NMuxCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+        selectors: one_hot([0, 0, 1, 0]),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![inputs[2]]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-        // Use `mux` to get `d = (b - a) * c + a
-        let d = field_chip.mux(&mut layouter, a, b, c)?;
+    let prover = MockProver::run(5, &circuit, vec![vec![inputs[1]]]).unwrap();
+    assert!(prover.verify().is_err());
 
-        // Expose the result as a public input to the circuit.
-        field_chip.expose_public(layouter.namespace(|| "expose d"), d, 0)
+    // Setting two selector bits is rejected, even when the output is consistent
+    // with the selected sum.
+    let circuit = NMuxCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+        selectors: one_hot([0, 1, 1, 0]),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![inputs[1] + inputs[2]]]).unwrap();
+    assert_fails_gate(&prover, "n-mux one-hot");
+    // ANCHOR_END: test-n-mux
+
+    // ANCHOR: test-is-zero
+    // Zero maps to 1 and a nonzero value maps to 0; the opposite claims fail.
+    for (a, expected) in [(Fp::zero(), Fp::one()), (Fp::rand(), Fp::zero())] {
+        let circuit = IsZeroCircuit { a: Some(a) };
+        let prover = MockProver::run(k, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::one() - expected]]).unwrap();
+        assert!(prover.verify().is_err());
     }
-}
-// ANCHOR_END: circuit
+    // ANCHOR_END: test-is-zero
 
-#[allow(clippy::many_single_char_names)]
-fn main() {
-    use halo2::{dev::MockProver, pasta::Fp};
+    // ANCHOR: test-equals
+    // Equal inputs give 1 and select `y`; unequal inputs give 0 and select `x`.
+    let a = Fp::rand();
+    let x = Fp::rand();
+    let y = Fp::rand();
+    for (b, expected) in [
+        (a, vec![Fp::one(), y]),
+        (a + Fp::one(), vec![Fp::zero(), x]),
+    ] {
+        let circuit = EqualsCircuit {
+            a: Some(a),
+            b: Some(b),
+            x: Some(x),
+            y: Some(y),
+        };
+        let prover = MockProver::run(5, &circuit, vec![expected.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = expected;
+        wrong[0] = Fp::one() - wrong[0];
+        let prover = MockProver::run(5, &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+    // ANCHOR_END: test-equals
 
-    // ANCHOR: test-circuit
-    // The number of rows in our circuit cannot exceed 2^k. Since our example
-    // circuit is very small, we can pick a very small value here.
-    let k = 4;
+    // ANCHOR: test-audited-branch
+    // The output follows `cond`, and the exposed flag must match the branch.
+    let a = Fp::rand();
+    let b = Fp::rand();
+    for (cond, expected) in [(Fp::one(), a), (Fp::zero(), b)] {
+        let circuit = AuditedBranchCircuit {
+            cond: Some(cond),
+            a: Some(a),
+            b: Some(b),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![expected, cond]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![expected, Fp::one() - cond]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+    // ANCHOR_END: test-audited-branch
+
+    // ANCHOR: test-find-first
+    // In [5, 7, 9, 9], 9 is first found at index 2 and 4 is not found at all.
+    let values: Vec<Option<Fp>> = [5, 7, 9, 9]
+        .iter()
+        .map(|&v| Some(Fp::from_u64(v)))
+        .collect();
+    for (target, found, index) in [(9, 1, 2), (4, 0, 0)] {
+        let circuit = FindFirstCircuit {
+            values: values.clone(),
+            target: Some(Fp::from_u64(target)),
+        };
+        let public_inputs = vec![Fp::from_u64(found), Fp::from_u64(index)];
+        let prover = MockProver::run(7, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let public_inputs = vec![Fp::from_u64(found), Fp::from_u64(index + 1)];
+        let prover = MockProver::run(7, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+    // ANCHOR_END: test-find-first
+
+    // ANCHOR: test-register
+    // Writing to register 2 (address bits `[0, 1]`) only changes the bank when
+    // the write is enabled.
+    let registers: Vec<Fp> = (0..4).map(|_| Fp::rand()).collect();
+    let value = Fp::rand();
+    for (enable, expected) in [
+        (
+            Fp::one(),
+            vec![registers[0], registers[1], value, registers[3]],
+        ),
+        (Fp::zero(), registers.clone()),
+    ] {
+        let circuit = RegisterCircuit {
+            registers: registers.iter().copied().map(Some).collect(),
+            write_addr_bits: vec![Some(Fp::zero()), Some(Fp::one())],
+            write_enable: Some(enable),
+            value: Some(value),
+        };
+        let prover = MockProver::run(6, &circuit, vec![expected.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Claiming the write landed in register 1 instead is rejected.
+        let mut public_inputs = expected;
+        public_inputs[1] = value;
+        let prover = MockProver::run(6, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 
-    /*
-     * (b - a) * c + a
-     * (cb - ca) + a
-     */
+    // Address bits beyond the width of `usize` are all high bits of the index,
+    // so they must be zero to match any register.
+    let mut write_addr_bits = vec![Some(Fp::zero()); 70];
+    write_addr_bits[0] = Some(Fp::one());
+    let circuit = RegisterCircuit {
+        registers: registers[..2].iter().copied().map(Some).collect(),
+        write_addr_bits,
+        write_enable: Some(Fp::one()),
+        value: Some(value),
+    };
+    let prover = MockProver::run(9, &circuit, vec![vec![registers[0], value]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    // ANCHOR_END: test-register
 
-    // Prepare the private and public inputs to the circuit!
+    // ANCHOR: test-cond-swap
+    // `swap = 0` keeps the order and `swap = 1` reverses it.
     let a = Fp::rand();
     let b = Fp::rand();
-    let c = Fp::rand();
-    let d = (b - a) * c;// + a;
+    for (swap, expected) in [(Fp::zero(), vec![a, b]), (Fp::one(), vec![b, a])] {
+        let circuit = CondSwapCircuit {
+            a: Some(a),
+            b: Some(b),
+            swap: Some(swap),
+        };
+        let prover = MockProver::run(k, &circuit, vec![expected.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![expected[1], expected[0]]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 
-    // Instantiate the circuit with the private inputs.
-    let circuit = MyCircuit {
+    // `swap = 2` is rejected even with outputs matching the mux arithmetic.
+    let swap = Fp::from_u64(2);
+    let circuit = CondSwapCircuit {
         a: Some(a),
         b: Some(b),
-        c: Some(b),
+        swap: Some(swap),
     };
+    let public_inputs = vec![(b - a) * swap + a, (a - b) * swap + b];
+    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    assert_fails_gate(&prover, "cond swap");
+    // ANCHOR_END: test-cond-swap
+
+    // ANCHOR: test-boolean
+    // Every row of the truth table, exposed as `[and, or, xor, not a]`.
+    for (a, b, and, or, xor, not) in [
+        (0, 0, 0, 0, 0, 1),
+        (0, 1, 0, 1, 1, 1),
+        (1, 0, 0, 1, 1, 0),
+        (1, 1, 1, 1, 0, 0),
+    ] {
+        let circuit = BooleanCircuit {
+            a: Some(Fp::from_u64(a)),
+            b: Some(Fp::from_u64(b)),
+        };
+        let mut public_inputs = vec![
+            Fp::from_u64(and),
+            Fp::from_u64(or),
+            Fp::from_u64(xor),
+            Fp::from_u64(not),
+        ];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Flipping any single output is caught.
+        for i in 0..public_inputs.len() {
+            public_inputs[i] = Fp::one() - public_inputs[i];
+            let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+            assert!(prover.verify().is_err());
+            public_inputs[i] = Fp::one() - public_inputs[i];
+        }
+    }
 
-    // Arrange the public input. We expose the mux result in row 0
-    // of the instance column, so we position it there in our public inputs.
-    let mut public_inputs = vec![d];
+    // Non-boolean inputs are rejected, even with outputs matching the formulas.
+    for (a, b) in [(Fp::from_u64(2), Fp::one()), (Fp::zero(), Fp::from_u64(3))] {
+        let circuit = BooleanCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        let public_inputs = vec![
+            a * b,
+            a + b - a * b,
+            a + b - Fp::from_u64(2) * a * b,
+            Fp::one() - a,
+        ];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_fails_gate(&prover, "and");
+    }
+    // ANCHOR_END: test-boolean
 
-    // Given the correct public input, our circuit will verify.
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    // ANCHOR: test-range-check
+    // 255 fits in 8 bits, but 256 does not.
+    let circuit = RangeCheckCircuit::<Fp, 8> {
+        value: Some(Fp::from_u64(255)),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 
-    // If we try some other public input, the proof will fail!
-    public_inputs[0] += Fp::one();
-    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    let circuit = RangeCheckCircuit::<Fp, 8> {
+        value: Some(Fp::from_u64(256)),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
     assert!(prover.verify().is_err());
-    // ANCHOR_END: test-circuit
-}
 
+    // A 0-bit range check is rejected rather than accepting every value.
+    let circuit = RangeCheckCircuit::<Fp, 0> {
+        value: Some(Fp::from_u64(1)),
+    };
+    assert!(MockProver::run(5, &circuit, vec![vec![]]).is_err());
+    // ANCHOR_END: test-range-check
+
+    // ANCHOR: test-compare
+    // For 8-bit inputs, `a > b` is exposed as 1 and anything else as 0.
+    for (a, b, gt) in [
+        (200, 100, 1),
+        (100, 200, 0),
+        (100, 100, 0),
+        (255, 0, 1),
+        (0, 255, 0),
+    ] {
+        let circuit = CompareCircuit::<Fp, 8> {
+            a: Some(Fp::from_u64(a)),
+            b: Some(Fp::from_u64(b)),
+        };
+        let prover = MockProver::run(6, &circuit, vec![vec![Fp::from_u64(gt)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(6, &circuit, vec![vec![Fp::from_u64(1 - gt)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Inputs that do not fit in 8 bits are rejected whichever answer is
+    // exposed, including ones the comparison gate alone would accept: `1 > -1`
+    // wraps around the modulus, and `256 > 0` has an 8-bit remainder.
+    for (a, b) in [
+        (Fp::from_u64(300), Fp::from_u64(1)),
+        (Fp::one(), -Fp::one()),
+        (Fp::from_u64(256), Fp::zero()),
+    ] {
+        let circuit = CompareCircuit::<Fp, 8> {
+            a: Some(a),
+            b: Some(b),
+        };
+        for gt in [0, 1] {
+            let prover = MockProver::run(6, &circuit, vec![vec![Fp::from_u64(gt)]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+    // ANCHOR_END: test-compare
+
+    // ANCHOR: test-decompose
+    // 0b1011 decomposes into the little-endian bits [1, 1, 0, 1].
+    let bits: Vec<Fp> = [1, 1, 0, 1].iter().map(|&b| Fp::from_u64(b)).collect();
+    let circuit = DecomposeCircuit {
+        value: Some(Fp::from_u64(0b1011)),
+        n: 4,
+    };
+    let prover = MockProver::run(k, &circuit, vec![bits.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let mut reversed = bits.clone();
+    reversed.reverse();
+    let prover = MockProver::run(k, &circuit, vec![reversed]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // 0b11011 needs 5 bits, so its low 4 bits do not recompose to it.
+    let circuit = DecomposeCircuit {
+        value: Some(Fp::from_u64(0b11011)),
+        n: 4,
+    };
+    let prover = MockProver::run(k, &circuit, vec![bits]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-decompose
+}
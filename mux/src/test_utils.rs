@@ -0,0 +1,148 @@
+//! Helpers for checking why `MockProver` rejects a circuit, and for recording
+//! where a circuit's layout assigns its cells.
+
+use halo2::{
+    arithmetic::FieldExt,
+    dev::{MockProver, VerifyFailure},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Selector,
+    },
+};
+
+/// Asserts that `prover` fails to verify, and that at least one failure is an
+/// unsatisfied constraint in the gate named `gate_name`.
+pub(crate) fn assert_fails_gate<F: FieldExt>(prover: &MockProver<F>, gate_name: &str) {
+    let failures = prover
+        .verify()
+        .expect_err("expected the circuit to fail verification");
+
+    // Constraint metadata only exposes the gate name through `Debug`, where it
+    // appears quoted.
+    let gate_name_debug = format!("{:?}", gate_name);
+    assert!(
+        failures.iter().any(|failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                format!("{:?}", constraint).contains(&gate_name_debug)
+            }
+            _ => false,
+        }),
+        "expected a failure in gate {:?}, got {:?}",
+        gate_name,
+        failures
+    );
+}
+
+/// The advice columns assigned by a circuit's layout, along with the
+/// namespaces they were assigned under, as recorded by
+/// [`record_advice_columns`].
+#[derive(Debug, Default)]
+pub(crate) struct AdviceColumns {
+    namespaces: Vec<String>,
+    assigned: Vec<(Vec<String>, Column<Advice>)>,
+}
+
+impl AdviceColumns {
+    /// Returns the columns of the cells assigned within the namespace named
+    /// `namespace`, in assignment order.
+    pub(crate) fn in_namespace(&self, namespace: &str) -> Vec<Column<Advice>> {
+        self.assigned
+            .iter()
+            .filter(|(namespaces, _)| namespaces.iter().any(|name| name == namespace))
+            .map(|(_, column)| *column)
+            .collect()
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for AdviceColumns {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.assigned.push((self.namespaces.clone(), column));
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+}
+
+/// Lays out `circuit` with its own floor planner and records the column of
+/// every advice cell it assigns.
+///
+/// No constant columns are passed to the floor planner, so circuits that
+/// assign cells from constants are not supported.
+pub(crate) fn record_advice_columns<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+) -> Result<AdviceColumns, Error> {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+
+    let mut columns = AdviceColumns::default();
+    <C::FloorPlanner as FloorPlanner>::synthesize(&mut columns, circuit, config, vec![])?;
+    Ok(columns)
+}
@@ -0,0 +1,96 @@
+//! Helpers shared by the chips and gadgets in the crate root.
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, Error},
+};
+
+use crate::Number;
+
+// ANCHOR: const-cell
+/// Assigns `value` into `column`, copy-constrained to the circuit's constant
+/// column so the prover cannot substitute a different value.
+pub(crate) fn const_cell<F: FieldExt>(
+    mut layouter: impl Layouter<F>,
+    column: Column<Advice>,
+    value: F,
+) -> Result<Number<F>, Error> {
+    let mut num = None;
+    layouter.assign_region(
+        || "const cell",
+        |mut region| {
+            let cell = region.assign_advice_from_constant(|| "constant", column, 0, value)?;
+            num = Some(Number {
+                cell,
+                value: Some(value),
+            });
+            Ok(())
+        },
+    )?;
+    Ok(num.unwrap())
+}
+
+/// Assigns zero into `column`, as in [`const_cell`].
+pub(crate) fn zero_cell<F: FieldExt>(
+    layouter: impl Layouter<F>,
+    column: Column<Advice>,
+) -> Result<Number<F>, Error> {
+    const_cell(layouter, column, F::zero())
+}
+// ANCHOR_END: const-cell
+
+// ANCHOR: load-private-from-bytes
+/// Loads the field element whose little-endian encoding is `bytes` as a
+/// private witness in `column`.
+///
+/// Returns `Error::SynthesisError` if `bytes` is not the canonical encoding of
+/// an element of `F`.
+pub(crate) fn load_private_from_bytes<F: FieldExt>(
+    mut layouter: impl Layouter<F>,
+    column: Column<Advice>,
+    bytes: &[u8; 32],
+) -> Result<Number<F>, Error> {
+    let value: Option<F> = F::from_bytes(bytes).into();
+    let value = value.ok_or(Error::SynthesisError)?;
+
+    let mut num = None;
+    layouter.assign_region(
+        || "load private from bytes",
+        |mut region| {
+            let cell = region.assign_advice(|| "private input", column, 0, || Ok(value))?;
+            num = Some(Number {
+                cell,
+                value: Some(value),
+            });
+            Ok(())
+        },
+    )?;
+    Ok(num.unwrap())
+}
+// ANCHOR_END: load-private-from-bytes
+
+// ANCHOR: assign-or-default
+/// Assigns `value` into `column` at `offset`, falling back to zero when the
+/// value is unknown.
+///
+/// This lets a circuit be synthesized without witnesses, for example to
+/// inspect its layout. It must not be used for values that are proven: a
+/// missing witness would then silently become zero rather than an error.
+pub(crate) fn assign_or_default<F: FieldExt, A, AR>(
+    region: &mut Region<'_, F>,
+    annotation: A,
+    column: Column<Advice>,
+    offset: usize,
+    value: Option<F>,
+) -> Result<Number<F>, Error>
+where
+    A: Fn() -> AR,
+    AR: Into<String>,
+{
+    let cell = region.assign_advice(annotation, column, offset, || {
+        Ok(value.unwrap_or_else(F::zero))
+    })?;
+    Ok(Number { cell, value })
+}
+// ANCHOR_END: assign-or-default
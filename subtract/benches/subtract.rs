@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use subtract::{build_circuit, keygen, prove, verify};
+
+fn bench_subtract(c: &mut Criterion) {
+    let mut group = c.benchmark_group("subtract");
+    group.sample_size(10);
+
+    for k in 4..=12 {
+        let (circuit, public_inputs) = build_circuit(k);
+
+        group.bench_with_input(BenchmarkId::new("keygen", k), &k, |b, &k| {
+            b.iter(|| keygen(k, &circuit).unwrap())
+        });
+
+        let (params, pk) = keygen(k, &circuit).unwrap();
+        group.bench_with_input(BenchmarkId::new("prove", k), &k, |b, _| {
+            b.iter(|| prove(&params, &pk, &circuit, &public_inputs).unwrap())
+        });
+
+        let proof = prove(&params, &pk, &circuit, &public_inputs).unwrap();
+        group.bench_with_input(BenchmarkId::new("verify", k), &k, |b, _| {
+            b.iter(|| assert!(verify(&params, pk.get_vk(), &proof, &public_inputs)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_subtract);
+criterion_main!(benches);
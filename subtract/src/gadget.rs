@@ -0,0 +1,7 @@
+//! Chips that `FieldChip` delegates to, besides the subtraction chip.
+
+pub(crate) mod add;
+pub(crate) mod affine;
+pub(crate) mod div;
+pub(crate) mod dual_op;
+pub(crate) mod mul;
@@ -0,0 +1,216 @@
+//! A chip adding two numbers, or summing many in a single region.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: add-instructions
+pub(crate) trait AddInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a + b`.
+    fn do_add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `inputs[0] + inputs[1] + ... + inputs[n - 1]`, laid out in a
+    /// single region of `n` rows with a running accumulator.
+    fn do_sum(&self, layouter: impl Layouter<F>, inputs: &[Self::Num]) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: add-instructions
+
+// ANCHOR: add-config
+#[derive(Clone, Debug)]
+pub(crate) struct AddConfig {
+    advice: [Column<Advice>; 2],
+    s_add: Selector,
+}
+// ANCHOR_END: add-config
+
+// ANCHOR: add-chip
+pub(crate) struct AddChip<F: FieldExt> {
+    config: AddConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: add-chip
+
+// ANCHOR: add-chip-trait-impl
+impl<F: FieldExt> Chip<F> for AddChip<F> {
+    type Config = AddConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: add-chip-trait-impl
+
+// ANCHOR: add-chip-impl
+impl<F: FieldExt> AddChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_add = meta.selector();
+
+        // Define our addition gate!
+        meta.create_gate("add", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_add = meta.query_selector(s_add);
+
+            vec![s_add * (lhs + rhs - out)]
+        });
+
+        AddConfig { advice, s_add }
+    }
+}
+// ANCHOR END: add-chip-impl
+
+// ANCHOR: add-instructions-impl
+impl<F: FieldExt> AddInstructions<F> for AddChip<F> {
+    type Num = Number<F>;
+
+    fn do_add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "add",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single addition gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_add.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+
+                // Now we can assign the addition result into the output position.
+                let value = a.value.and_then(|a| b.value.map(|b| a + b));
+                let cell = region.assign_advice(
+                    || "lhs + rhs",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                // Finally, we return a variable representing the output,
+                // to be used in another part of the circuit.
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let (first, rest) = inputs.split_first().ok_or(Error::SynthesisError)?;
+
+        let mut out = None;
+        layouter.assign_region(
+            || "sum",
+            |mut region: Region<'_, F>| {
+                // As in `do_subtract_inline`, each output is the next row's `lhs`:
+                //
+                // | a0        | a1        | s_add |
+                // |-----------|-----------|-------|
+                // | inputs[0] | inputs[1] | 1     |
+                // | acc_1     | inputs[2] | 1     |
+                // | ...       | ...       | ...   |
+                // | acc_n     |           | 0     |
+                let mut acc_value = first.value;
+                let mut acc = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || acc_value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(first.cell, acc)?;
+
+                for (row, b) in rest.iter().enumerate() {
+                    config.s_add.enable(&mut region, row)?;
+
+                    let rhs = region.assign_advice(
+                        || format!("rhs {}", row),
+                        config.advice[1],
+                        row,
+                        || b.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(b.cell, rhs)?;
+
+                    acc_value = acc_value.and_then(|a| b.value.map(|b| a + b));
+                    acc = region.assign_advice(
+                        || format!("lhs + rhs {}", row),
+                        config.advice[0],
+                        row + 1,
+                        || acc_value.ok_or(Error::SynthesisError),
+                    )?;
+                }
+
+                out = Some(Number {
+                    cell: acc,
+                    value: acc_value,
+                });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: add-instructions-impl
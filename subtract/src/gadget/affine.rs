@@ -0,0 +1,355 @@
+//! A chip for affine and quadratic relations with constant coefficients.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: affine-instructions
+pub(crate) trait AffineInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Constrains `y = m * x + b` for the constants `m` and `b`.
+    fn do_assert_on_line(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        m: F,
+        b: F,
+    ) -> Result<(), Error>;
+
+    /// Returns `out = c0 * x + c1 * y` for the constants `c0` and `c1`.
+    fn do_linear_combination(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        c0: F,
+        c1: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `out = c * a` for the constant `c`.
+    fn do_mul_by_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        c: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Constrains `a * x^2 + b * x + c = 0` for the constants `a`, `b` and `c`.
+    fn do_assert_quadratic(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<(), Error>;
+}
+// ANCHOR_END: affine-instructions
+
+// ANCHOR: affine-config
+#[derive(Clone, Debug)]
+pub(crate) struct AffineConfig {
+    advice: [Column<Advice>; 2],
+    /// Fixed columns holding the constants of each affine gate.
+    coeffs: [Column<Fixed>; 2],
+    s_affine: Selector,
+    s_linear: Selector,
+    s_mulc: Selector,
+    s_quadratic: Selector,
+}
+// ANCHOR_END: affine-config
+
+// ANCHOR: affine-chip
+pub(crate) struct AffineChip<F: FieldExt> {
+    config: AffineConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: affine-chip
+
+// ANCHOR: affine-chip-trait-impl
+impl<F: FieldExt> Chip<F> for AffineChip<F> {
+    type Config = AffineConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: affine-chip-trait-impl
+
+// ANCHOR: affine-chip-impl
+impl<F: FieldExt> AffineChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let coeffs = [meta.fixed_column(), meta.fixed_column()];
+        let s_affine = meta.selector();
+        let s_linear = meta.selector();
+        let s_mulc = meta.selector();
+        let s_quadratic = meta.selector();
+
+        // Define our affine gate! The constants live in fixed columns on the
+        // same row as the point:
+        //
+        // | a0 | a1 | f0 | f1 | s_affine |
+        // |----|----|----|----|----------|
+        // | x  | y  | m  | b  | s_affine |
+        meta.create_gate("affine", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let y = meta.query_advice(advice[1], Rotation::cur());
+            let m = meta.query_fixed(coeffs[0], Rotation::cur());
+            let b = meta.query_fixed(coeffs[1], Rotation::cur());
+            let s_affine = meta.query_selector(s_affine);
+
+            vec![s_affine * (m * x + b - y)]
+        });
+
+        // The linear combination gate reuses the same fixed columns for its
+        // coefficients, and places the output on the next row:
+        //
+        // | a0  | a1 | f0 | f1 | s_linear |
+        // |-----|----|----|----|----------|
+        // | x   | y  | c0 | c1 | s_linear |
+        // | out |    |    |    |          |
+        meta.create_gate("linear combination", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let y = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let c0 = meta.query_fixed(coeffs[0], Rotation::cur());
+            let c1 = meta.query_fixed(coeffs[1], Rotation::cur());
+            let s_linear = meta.query_selector(s_linear);
+
+            vec![s_linear * (c0 * x + c1 * y - out)]
+        });
+
+        // Scaling by a constant only needs the first coefficient column:
+        //
+        // | a0 | a1  | f0 | s_mulc |
+        // |----|-----|----|--------|
+        // | a  | out | c  | s_mulc |
+        meta.create_gate("mul by constant", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_fixed(coeffs[0], Rotation::cur());
+            let s_mulc = meta.query_selector(s_mulc);
+
+            vec![s_mulc * (out - c * a)]
+        });
+
+        // The quadratic gate needs a third constant, which goes in the first
+        // coefficient column on the next row:
+        //
+        // | a0 | f0 | f1 | s_quadratic |
+        // |----|----|----|-------------|
+        // | x  | a  | b  | s_quadratic |
+        // |    | c  |    |             |
+        meta.create_gate("quadratic", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let a = meta.query_fixed(coeffs[0], Rotation::cur());
+            let b = meta.query_fixed(coeffs[1], Rotation::cur());
+            let c = meta.query_fixed(coeffs[0], Rotation::next());
+            let s_quadratic = meta.query_selector(s_quadratic);
+
+            vec![s_quadratic * (a * x.clone() * x.clone() + b * x + c)]
+        });
+
+        AffineConfig {
+            advice,
+            coeffs,
+            s_affine,
+            s_linear,
+            s_mulc,
+            s_quadratic,
+        }
+    }
+}
+// ANCHOR END: affine-chip-impl
+
+// ANCHOR: affine-instructions-impl
+impl<F: FieldExt> AffineInstructions<F> for AffineChip<F> {
+    type Num = Number<F>;
+
+    fn do_assert_on_line(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        m: F,
+        b: F,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "assert on line",
+            |mut region: Region<'_, F>| {
+                config.s_affine.enable(&mut region, 0)?;
+
+                let x_cell = region.assign_advice(
+                    || "x",
+                    config.advice[0],
+                    0,
+                    || x.value.ok_or(Error::SynthesisError),
+                )?;
+                let y_cell = region.assign_advice(
+                    || "y",
+                    config.advice[1],
+                    0,
+                    || y.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(x.cell, x_cell)?;
+                region.constrain_equal(y.cell, y_cell)?;
+
+                region.assign_fixed(|| "m", config.coeffs[0], 0, || Ok(m))?;
+                region.assign_fixed(|| "b", config.coeffs[1], 0, || Ok(b))?;
+                Ok(())
+            },
+        )
+    }
+
+    fn do_linear_combination(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        c0: F,
+        c1: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "linear combination",
+            |mut region: Region<'_, F>| {
+                config.s_linear.enable(&mut region, 0)?;
+
+                let x_cell = region.assign_advice(
+                    || "x",
+                    config.advice[0],
+                    0,
+                    || x.value.ok_or(Error::SynthesisError),
+                )?;
+                let y_cell = region.assign_advice(
+                    || "y",
+                    config.advice[1],
+                    0,
+                    || y.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(x.cell, x_cell)?;
+                region.constrain_equal(y.cell, y_cell)?;
+
+                region.assign_fixed(|| "c0", config.coeffs[0], 0, || Ok(c0))?;
+                region.assign_fixed(|| "c1", config.coeffs[1], 0, || Ok(c1))?;
+
+                let value = x.value.and_then(|x| y.value.map(|y| c0 * x + c1 * y));
+                let cell = region.assign_advice(
+                    || "c0 * x + c1 * y",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_mul_by_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        c: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "mul by constant",
+            |mut region: Region<'_, F>| {
+                config.s_mulc.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+
+                region.assign_fixed(|| "c", config.coeffs[0], 0, || Ok(c))?;
+
+                let value = a.value.map(|a| c * a);
+                let cell = region.assign_advice(
+                    || "c * a",
+                    config.advice[1],
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_assert_quadratic(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "assert quadratic",
+            |mut region: Region<'_, F>| {
+                config.s_quadratic.enable(&mut region, 0)?;
+
+                let x_cell = region.assign_advice(
+                    || "x",
+                    config.advice[0],
+                    0,
+                    || x.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(x.cell, x_cell)?;
+
+                region.assign_fixed(|| "a", config.coeffs[0], 0, || Ok(a))?;
+                region.assign_fixed(|| "b", config.coeffs[1], 0, || Ok(b))?;
+                region.assign_fixed(|| "c", config.coeffs[0], 1, || Ok(c))?;
+                Ok(())
+            },
+        )
+    }
+}
+// ANCHOR_END: affine-instructions-impl
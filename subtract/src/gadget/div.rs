@@ -0,0 +1,163 @@
+//! A chip dividing one number by a nonzero other.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: div-instructions
+pub(crate) trait DivInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a * b^{-1}`. Fails if `b` is zero.
+    fn do_div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: div-instructions
+
+// ANCHOR: div-config
+#[derive(Clone, Debug)]
+pub(crate) struct DivConfig {
+    advice: [Column<Advice>; 2],
+    s_div: Selector,
+}
+// ANCHOR_END: div-config
+
+// ANCHOR: div-chip
+pub(crate) struct DivChip<F: FieldExt> {
+    config: DivConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: div-chip
+
+// ANCHOR: div-chip-trait-impl
+impl<F: FieldExt> Chip<F> for DivChip<F> {
+    type Config = DivConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: div-chip-trait-impl
+
+// ANCHOR: div-chip-impl
+impl<F: FieldExt> DivChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_div = meta.selector();
+
+        // Define our division gate!
+        meta.create_gate("div", |meta| {
+            // | a0  | a1  | s_div |
+            // |-----|-----|-------|
+            // | lhs | rhs | s_div |
+            // | out | inv |       |
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let inv = meta.query_advice(advice[1], Rotation::next());
+            let s_div = meta.query_selector(s_div);
+
+            vec![
+                // `inv` is the inverse of `rhs`, so `rhs` cannot be zero.
+                s_div.clone() * (rhs * inv.clone() - Expression::Constant(F::one())),
+                s_div * (lhs * inv - out),
+            ]
+        });
+
+        DivConfig { advice, s_div }
+    }
+}
+// ANCHOR END: div-chip-impl
+
+// ANCHOR: div-instructions-impl
+impl<F: FieldExt> DivInstructions<F> for DivChip<F> {
+    type Num = Number<F>;
+
+    fn do_div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        // Zero has no inverse, so there is no witness that satisfies the gate.
+        let inv = match b.value {
+            Some(b) => Some(Option::<F>::from(b.invert()).ok_or(Error::SynthesisError)?),
+            None => None,
+        };
+
+        let mut out = None;
+        layouter.assign_region(
+            || "div",
+            |mut region: Region<'_, F>| {
+                config.s_div.enable(&mut region, 0)?;
+
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+
+                region.assign_advice(
+                    || "rhs^-1",
+                    config.advice[1],
+                    1,
+                    || inv.ok_or(Error::SynthesisError),
+                )?;
+
+                let value = a.value.and_then(|a| inv.map(|inv| a * inv));
+                let cell = region.assign_advice(
+                    || "lhs * rhs^-1",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: div-instructions-impl
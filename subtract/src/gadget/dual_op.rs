@@ -0,0 +1,171 @@
+//! A chip computing both the sum and the difference of two numbers in one row.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: dual-op-instructions
+pub(crate) trait DualOpInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `out = sel * (a + b) + (1 - sel) * (a - b)` for a boolean `sel`.
+    fn do_dual_op_select(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        sel: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: dual-op-instructions
+
+// ANCHOR: dual-op-config
+#[derive(Clone, Debug)]
+pub(crate) struct DualOpConfig {
+    advice: [Column<Advice>; 2],
+    s_dual_op: Selector,
+}
+// ANCHOR_END: dual-op-config
+
+// ANCHOR: dual-op-chip
+pub(crate) struct DualOpChip<F: FieldExt> {
+    config: DualOpConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: dual-op-chip
+
+// ANCHOR: dual-op-chip-trait-impl
+impl<F: FieldExt> Chip<F> for DualOpChip<F> {
+    type Config = DualOpConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: dual-op-chip-trait-impl
+
+// ANCHOR: dual-op-chip-impl
+impl<F: FieldExt> DualOpChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_dual_op = meta.selector();
+
+        // Define our dual-op gate! Both operations share the `a - b` term:
+        //
+        //     sel * (a + b) + (1 - sel) * (a - b) = a - b + 2 * sel * b
+        //
+        // so one degree-3 constraint replaces an add, a subtract and a mux.
+        //
+        // | a0  | a1  | s_dual_op |
+        // |-----|-----|-----------|
+        // | a   | b   | s_dual_op |
+        // | sel | out |           |
+        meta.create_gate("dual op", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let sel = meta.query_advice(advice[0], Rotation::next());
+            let out = meta.query_advice(advice[1], Rotation::next());
+            let s_dual_op = meta.query_selector(s_dual_op);
+
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from_u64(2));
+
+            vec![
+                s_dual_op.clone() * (a - b.clone() + two * sel.clone() * b - out),
+                // The selector must be boolean for the output to be one of the two
+                // operations.
+                s_dual_op * sel.clone() * (one - sel),
+            ]
+        });
+
+        DualOpConfig { advice, s_dual_op }
+    }
+}
+// ANCHOR END: dual-op-chip-impl
+
+// ANCHOR: dual-op-instructions-impl
+impl<F: FieldExt> DualOpInstructions<F> for DualOpChip<F> {
+    type Num = Number<F>;
+
+    fn do_dual_op_select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        sel: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "dual op",
+            |mut region: Region<'_, F>| {
+                config.s_dual_op.enable(&mut region, 0)?;
+
+                let a_cell = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let b_cell = region.assign_advice(
+                    || "b",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                let sel_cell = region.assign_advice(
+                    || "sel",
+                    config.advice[0],
+                    1,
+                    || sel.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, a_cell)?;
+                region.constrain_equal(b.cell, b_cell)?;
+                region.constrain_equal(sel.cell, sel_cell)?;
+
+                let value = a.value.and_then(|a| {
+                    b.value
+                        .and_then(|b| sel.value.map(|sel| a - b + F::from_u64(2) * sel * b))
+                });
+                let cell = region.assign_advice(
+                    || "sel ? a + b : a - b",
+                    config.advice[1],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: dual-op-instructions-impl
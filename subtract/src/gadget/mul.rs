@@ -0,0 +1,324 @@
+//! A chip multiplying and squaring numbers, and taking dot products.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// ANCHOR: mul-instructions
+pub(crate) trait MulInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a * b`.
+    fn do_mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * a`.
+    fn do_square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `a[0] * b[0] + ... + a[n - 1] * b[n - 1]`, laid out in a single
+    /// region of `2n` rows with a running accumulator. `a` and `b` must be
+    /// non-empty and have the same length.
+    fn do_dot(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: mul-instructions
+
+// ANCHOR: mul-config
+#[derive(Clone, Debug)]
+pub(crate) struct MulConfig {
+    advice: [Column<Advice>; 2],
+    s_mul: Selector,
+    s_sq: Selector,
+    s_mac: Selector,
+}
+// ANCHOR_END: mul-config
+
+// ANCHOR: mul-chip
+pub(crate) struct MulChip<F: FieldExt> {
+    config: MulConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: mul-chip
+
+// ANCHOR: mul-chip-trait-impl
+impl<F: FieldExt> Chip<F> for MulChip<F> {
+    type Config = MulConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: mul-chip-trait-impl
+
+// ANCHOR: mul-chip-impl
+impl<F: FieldExt> MulChip<F> {
+    pub(crate) fn construct(
+        config: <Self as Chip<F>>::Config,
+        _loaded: <Self as Chip<F>>::Loaded,
+    ) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_mul = meta.selector();
+
+        // Define our multiplication gate!
+        meta.create_gate("mul", |meta| {
+            // | a0  | a1  | s_mul |
+            // |-----|-----|-------|
+            // | lhs | rhs | s_mul |
+            // | out |     |       |
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul = meta.query_selector(s_mul);
+
+            vec![s_mul * (lhs * rhs - out)]
+        });
+
+        let s_sq = meta.selector();
+
+        // Squaring only needs its input once, so it only occupies `advice[0]`:
+        //
+        // | a0  | s_sq |
+        // |-----|------|
+        // | a   | s_sq |
+        // | out |      |
+        meta.create_gate("square", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_sq = meta.query_selector(s_sq);
+
+            vec![s_sq * (out - a.clone() * a)]
+        });
+
+        let s_mac = meta.selector();
+
+        // Reusing the mul and add gates for a dot product would not fit one
+        // region: both gates write their output to `advice[0]` on the next
+        // row, but the add gate reads its second input from `advice[1]`. Each
+        // product would need another row and a copy before it could be added
+        // to the running total, and the add gate's selector belongs to the add
+        // chip's config. The dot product therefore uses its own gate.
+        //
+        // Multiply-accumulate adds a product to the accumulator two rows up,
+        // so that a chain of them alternates operand and accumulator rows:
+        //
+        // | a0      | a1  | s_mac |
+        // |---------|-----|-------|
+        // | acc_in  |     |       |
+        // | lhs     | rhs | s_mac |
+        // | acc_out |     |       |
+        meta.create_gate("multiply accumulate", |meta| {
+            let acc_in = meta.query_advice(advice[0], Rotation::prev());
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let acc_out = meta.query_advice(advice[0], Rotation::next());
+            let s_mac = meta.query_selector(s_mac);
+
+            vec![s_mac * (acc_in + lhs * rhs - acc_out)]
+        });
+
+        MulConfig {
+            advice,
+            s_mul,
+            s_sq,
+            s_mac,
+        }
+    }
+}
+// ANCHOR END: mul-chip-impl
+
+// ANCHOR: mul-instructions-impl
+impl<F: FieldExt> MulInstructions<F> for MulChip<F> {
+    type Num = Number<F>;
+
+    fn do_mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "mul",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single multiplication gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_mul.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+
+                // Now we can assign the multiplication result into the output position.
+                let value = a.value.and_then(|a| b.value.map(|b| a * b));
+                let cell = region.assign_advice(
+                    || "lhs * rhs",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                // Finally, we return a variable representing the output,
+                // to be used in another part of the circuit.
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_square(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "square",
+            |mut region: Region<'_, F>| {
+                config.s_sq.enable(&mut region, 0)?;
+
+                // Unlike `do_mul`, the input is copied in only once.
+                let input = region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, input)?;
+
+                let value = a.value.map(|a| a.square());
+                let cell = region.assign_advice(
+                    || "a * a",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_dot(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        if a.is_empty() || a.len() != b.len() {
+            return Err(Error::SynthesisError);
+        }
+
+        let mut out = None;
+        layouter.assign_region(
+            || "dot",
+            |mut region: Region<'_, F>| {
+                // The first product uses the plain multiplication gate, and
+                // each later one accumulates onto it:
+                //
+                // | a0    | a1   | s_mul | s_mac |
+                // |-------|------|-------|-------|
+                // | a[0]  | b[0] | 1     | 0     |
+                // | acc_0 |      | 0     | 0     |
+                // | a[1]  | b[1] | 0     | 1     |
+                // | acc_1 |      | 0     | 0     |
+                // | ...   | ...  | ...   | ...   |
+                let mut acc_value = Some(F::zero());
+                let mut acc = None;
+                for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                    let row = 2 * i;
+                    if i == 0 {
+                        config.s_mul.enable(&mut region, row)?;
+                    } else {
+                        config.s_mac.enable(&mut region, row)?;
+                    }
+
+                    let lhs = region.assign_advice(
+                        || format!("lhs {}", i),
+                        config.advice[0],
+                        row,
+                        || a.value.ok_or(Error::SynthesisError),
+                    )?;
+                    let rhs = region.assign_advice(
+                        || format!("rhs {}", i),
+                        config.advice[1],
+                        row,
+                        || b.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(a.cell, lhs)?;
+                    region.constrain_equal(b.cell, rhs)?;
+
+                    acc_value = acc_value
+                        .and_then(|acc| a.value.and_then(|a| b.value.map(|b| acc + a * b)));
+                    acc = Some(region.assign_advice(
+                        || format!("acc {}", i),
+                        config.advice[0],
+                        row + 1,
+                        || acc_value.ok_or(Error::SynthesisError),
+                    )?);
+                }
+
+                out = Some(Number {
+                    cell: acc.unwrap(),
+                    value: acc_value,
+                });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR_END: mul-instructions-impl
@@ -0,0 +1,2227 @@
+//! Arithmetic chips built around subtraction, and example circuits using them.
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Chip, Layouter, Region, SimpleFloorPlanner},
+    pasta::{self, EqAffine},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, Fixed, Instance, ProvingKey, Selector, SingleVerifier,
+        VerifyingKey,
+    },
+    poly::{commitment::Params, Rotation},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+
+mod gadget;
+use gadget::add::{AddChip, AddConfig, AddInstructions};
+use gadget::affine::{AffineChip, AffineConfig, AffineInstructions};
+use gadget::div::{DivChip, DivConfig, DivInstructions};
+use gadget::dual_op::{DualOpChip, DualOpConfig, DualOpInstructions};
+use gadget::mul::{MulChip, MulConfig, MulInstructions};
+
+// ANCHOR: field-instructions
+/// A variable representing a number.
+#[derive(Clone)]
+struct Number<F: FieldExt> {
+    cell: Cell,
+    value: Option<F>,
+}
+
+trait FieldInstructions<F: FieldExt>:
+    SubtractInstructions<F>
+    + AddInstructions<F>
+    + MulInstructions<F>
+    + DivInstructions<F>
+    + AffineInstructions<F>
+    + DualOpInstructions<F>
+{
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Option<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Loads a constant into the circuit.
+    fn load_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = a - b`.
+    fn subtract(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = -a`.
+    fn negate(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns the running difference `inputs[0] - inputs[1] - ...`.
+    fn subtract_chain(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns the sum of `inputs`, or zero if `inputs` is empty.
+    fn sum(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = a + b`.
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns the dot product of `a` and `b`, which must have the same length,
+    /// in a single multiply-accumulate region.
+    fn dot(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &[<Self as FieldInstructions<F>>::Num],
+        b: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = a * b`.
+    fn multiply(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = a * a`.
+    fn square(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns the S-box `x^5`.
+    fn sbox(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `base^exp` for the fixed exponent `exp`, by square-and-multiply.
+    fn pow_fixed(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        base: <Self as FieldInstructions<F>>::Num,
+        exp: u64,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `d = a / b`, constraining `b` to be nonzero.
+    fn divide(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `a + b` if `sel` is 1, or `a - b` if `sel` is 0.
+    fn dual_op_select(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        sel: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Asserts that the point `(x, y)` lies on the fixed line `y = m * x + b`.
+    fn assert_on_line(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+        y: <Self as FieldInstructions<F>>::Num,
+        m: F,
+        b: F,
+    ) -> Result<(), Error>;
+
+    /// Asserts that `x` is a root of the fixed quadratic `a * x^2 + b * x + c`.
+    fn assert_quadratic(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<(), Error>;
+
+    /// Returns `d = c * a` for the constant `c`.
+    fn mul_by_constant(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        c: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `values` permuted by bit-reversing their indices. The length of
+    /// `values` must be a power of two.
+    fn bit_reverse(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error>;
+
+    /// Asserts that `output[i] == input[perm[i]]` for the public permutation
+    /// `perm`.
+    fn assert_shuffle(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        input: &[<Self as FieldInstructions<F>>::Num],
+        output: &[<Self as FieldInstructions<F>>::Num],
+        perm: &[usize],
+    ) -> Result<(), Error>;
+
+    /// Returns the output of a 2-tap FIR filter, `c0 * x_cur + c1 * x_prev`.
+    fn fir2(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x_prev: <Self as FieldInstructions<F>>::Num,
+        x_cur: <Self as FieldInstructions<F>>::Num,
+        c0: F,
+        c1: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Applies `fir2` across `values`, with the delayed sample starting at zero.
+    fn fir_stream(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+        c0: F,
+        c1: F,
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error>;
+
+    /// Reconstructs a secret from `(x, y)` shares of a polynomial by evaluating
+    /// its Lagrange interpolation at `x = 0`. The share positions are public.
+    fn reconstruct_secret(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        shares: &[(F, <Self as FieldInstructions<F>>::Num)],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Asserts that the polynomial with coefficients `coeffs` (lowest degree
+    /// first) evaluates to `claimed` at the fixed point `z`.
+    fn assert_poly_eval(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        coeffs: &[<Self as FieldInstructions<F>>::Num],
+        z: F,
+        claimed: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error>;
+
+    /// Constrains `a` and `b` to be equal, without producing a new cell.
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error>;
+
+    /// Asserts that the field sum of `data` equals `expected`.
+    fn assert_checksum(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        data: &[<Self as FieldInstructions<F>>::Num],
+        expected: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+
+    /// Exposes each number as a public input at its paired instance row.
+    fn expose_public_batch(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[(<Self as FieldInstructions<F>>::Num, usize)],
+    ) -> Result<(), Error>;
+}
+// ANCHOR_END: field-instructions
+
+// ANCHOR: subtract-instructions
+trait SubtractInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a - b`.
+    fn do_subtract(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `inputs[0] - inputs[1] - ... - inputs[n - 1]`, laid out in a
+    /// single region where each output doubles as the next `lhs`.
+    fn do_subtract_inline(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: subtract-instructions
+
+// ANCHOR: field-config
+// The top-level config that provides all necessary columns and permutations
+// for the other configs.
+#[derive(Clone, Debug)]
+pub struct FieldConfig {
+    /// For this chip, we will use two advice columns to implement our instructions.
+    /// These are also the columns through which we communicate with other parts of
+    /// the circuit.
+    advice: [Column<Advice>; 2],
+
+    /// Public inputs
+    instance: Column<Instance>,
+
+    /// Constants, with equality enabled so they can be copied into advice cells.
+    constant: Column<Fixed>,
+
+    subtract_config: SubtractConfig,
+    add_config: AddConfig,
+    mul_config: MulConfig,
+    div_config: DivConfig,
+    affine_config: AffineConfig,
+    dual_op_config: DualOpConfig,
+}
+// ANCHOR END: field-config
+
+// ANCHOR: subtract-config
+#[derive(Clone, Debug)]
+struct SubtractConfig {
+    advice: [Column<Advice>; 2],
+    s_subtract: Selector,
+}
+// ANCHOR_END: subtract-config
+
+// ANCHOR: field-chip
+/// The top-level chip that will implement the `FieldInstructions`.
+struct FieldChip<F: FieldExt> {
+    config: FieldConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: field-chip
+
+// ANCHOR: subtract-chip
+struct SubtractChip<F: FieldExt> {
+    config: SubtractConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: subtract-chip
+
+// ANCHOR: subtract-chip-trait-impl
+impl<F: FieldExt> Chip<F> for SubtractChip<F> {
+    type Config = SubtractConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: subtract-chip-trait-impl
+
+// ANCHOR: subtract-chip-impl
+impl<F: FieldExt> SubtractChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_subtract = meta.selector();
+
+        // Define our subtraction gate!
+        meta.create_gate("subtract", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_subtract = meta.query_selector(s_subtract);
+
+            vec![s_subtract * (lhs - rhs - out)]
+        });
+
+        SubtractConfig { advice, s_subtract }
+    }
+}
+// ANCHOR END: subtract-chip-impl
+
+// ANCHOR: subtract-instructions-impl
+impl<F: FieldExt> SubtractInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_subtract(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().subtract_config.clone();
+
+        let subtract_chip = SubtractChip::<F>::construct(config, ());
+        subtract_chip.do_subtract(layouter, a, b)
+    }
+
+    fn do_subtract_inline(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().subtract_config.clone();
+
+        let subtract_chip = SubtractChip::<F>::construct(config, ());
+        subtract_chip.do_subtract_inline(layouter, inputs)
+    }
+}
+
+impl<F: FieldExt> SubtractInstructions<F> for SubtractChip<F> {
+    type Num = Number<F>;
+
+    fn do_subtract(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let mut out = None;
+        layouter.assign_region(
+            || "subtract",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single subtraction gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_subtract.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || a.value.ok_or(Error::SynthesisError),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || b.value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(a.cell, lhs)?;
+                region.constrain_equal(b.cell, rhs)?;
+
+                // Now we can assign the subtraction result into the output position.
+                let value = a.value.and_then(|a| b.value.map(|b| a - b));
+                let cell = region.assign_advice(
+                    || "lhs - rhs",
+                    config.advice[0],
+                    1,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+
+                // Finally, we return a variable representing the output,
+                // to be used in another part of the circuit.
+                out = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+
+    fn do_subtract_inline(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let (first, rest) = inputs.split_first().ok_or(Error::SynthesisError)?;
+
+        let mut out = None;
+        layouter.assign_region(
+            || "subtract inline",
+            |mut region: Region<'_, F>| {
+                // The subtraction gate writes its output to `advice[0]` on the next
+                // row, which is exactly where the next gate reads its `lhs`:
+                //
+                // | a0        | a1        | s_subtract |
+                // |-----------|-----------|------------|
+                // | inputs[0] | inputs[1] | 1          |
+                // | acc_1     | inputs[2] | 1          |
+                // | ...       | ...       | ...        |
+                // | acc_n     |           | 0          |
+                let mut acc_value = first.value;
+                let mut acc = region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || acc_value.ok_or(Error::SynthesisError),
+                )?;
+                region.constrain_equal(first.cell, acc)?;
+
+                for (row, b) in rest.iter().enumerate() {
+                    config.s_subtract.enable(&mut region, row)?;
+
+                    let rhs = region.assign_advice(
+                        || format!("rhs {}", row),
+                        config.advice[1],
+                        row,
+                        || b.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(b.cell, rhs)?;
+
+                    acc_value = acc_value.and_then(|a| b.value.map(|b| a - b));
+                    acc = region.assign_advice(
+                        || format!("lhs - rhs {}", row),
+                        config.advice[0],
+                        row + 1,
+                        || acc_value.ok_or(Error::SynthesisError),
+                    )?;
+                }
+
+                out = Some(Number {
+                    cell: acc,
+                    value: acc_value,
+                });
+                Ok(())
+            },
+        )?;
+
+        Ok(out.unwrap())
+    }
+}
+// ANCHOR END: subtract-instructions-impl
+
+// ANCHOR: add-instructions-impl
+impl<F: FieldExt> AddInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().add_config.clone();
+
+        let add_chip = AddChip::<F>::construct(config, ());
+        add_chip.do_add(layouter, a, b)
+    }
+
+    fn do_sum(&self, layouter: impl Layouter<F>, inputs: &[Self::Num]) -> Result<Self::Num, Error> {
+        let config = self.config().add_config.clone();
+
+        let add_chip = AddChip::<F>::construct(config, ());
+        add_chip.do_sum(layouter, inputs)
+    }
+}
+// ANCHOR END: add-instructions-impl
+
+// ANCHOR: mul-instructions-impl
+impl<F: FieldExt> MulInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().mul_config.clone();
+
+        let mul_chip = MulChip::<F>::construct(config, ());
+        mul_chip.do_mul(layouter, a, b)
+    }
+
+    fn do_square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config().mul_config.clone();
+
+        let mul_chip = MulChip::<F>::construct(config, ());
+        mul_chip.do_square(layouter, a)
+    }
+
+    fn do_dot(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().mul_config.clone();
+
+        let mul_chip = MulChip::<F>::construct(config, ());
+        mul_chip.do_dot(layouter, a, b)
+    }
+}
+// ANCHOR END: mul-instructions-impl
+
+// ANCHOR: div-instructions-impl
+impl<F: FieldExt> DivInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().div_config.clone();
+
+        let div_chip = DivChip::<F>::construct(config, ());
+        div_chip.do_div(layouter, a, b)
+    }
+}
+// ANCHOR END: div-instructions-impl
+
+// ANCHOR: affine-instructions-impl
+impl<F: FieldExt> AffineInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_assert_on_line(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        m: F,
+        b: F,
+    ) -> Result<(), Error> {
+        let config = self.config().affine_config.clone();
+
+        let affine_chip = AffineChip::<F>::construct(config, ());
+        affine_chip.do_assert_on_line(layouter, x, y, m, b)
+    }
+
+    fn do_linear_combination(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        c0: F,
+        c1: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().affine_config.clone();
+
+        let affine_chip = AffineChip::<F>::construct(config, ());
+        affine_chip.do_linear_combination(layouter, x, y, c0, c1)
+    }
+
+    fn do_mul_by_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        c: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().affine_config.clone();
+
+        let affine_chip = AffineChip::<F>::construct(config, ());
+        affine_chip.do_mul_by_constant(layouter, a, c)
+    }
+
+    fn do_assert_quadratic(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<(), Error> {
+        let config = self.config().affine_config.clone();
+
+        let affine_chip = AffineChip::<F>::construct(config, ());
+        affine_chip.do_assert_quadratic(layouter, x, a, b, c)
+    }
+}
+// ANCHOR END: affine-instructions-impl
+
+// ANCHOR: dual-op-instructions-impl
+impl<F: FieldExt> DualOpInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn do_dual_op_select(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        sel: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().dual_op_config.clone();
+
+        let dual_op_chip = DualOpChip::<F>::construct(config, ());
+        dual_op_chip.do_dual_op_select(layouter, a, b, sel)
+    }
+}
+// ANCHOR END: dual-op-instructions-impl
+
+// ANCHOR: field-chip-trait-impl
+impl<F: FieldExt> Chip<F> for FieldChip<F> {
+    type Config = FieldConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: field-chip-trait-impl
+
+// ANCHOR: field-chip-impl
+impl<F: FieldExt> FieldChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+    ) -> <Self as Chip<F>>::Config {
+        let subtract_config = SubtractChip::configure(meta, advice);
+        let add_config = AddChip::configure(meta, advice);
+        let mul_config = MulChip::configure(meta, advice);
+        let div_config = DivChip::configure(meta, advice);
+        let affine_config = AffineChip::configure(meta, advice);
+        let dual_op_config = DualOpChip::configure(meta, advice);
+
+        meta.enable_equality(instance.into());
+        for column in &advice {
+            meta.enable_equality((*column).into());
+        }
+
+        // Like the affine coefficients, the constant column is internal to the chip.
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        FieldConfig {
+            advice,
+            instance,
+            constant,
+            subtract_config,
+            add_config,
+            mul_config,
+            div_config,
+            affine_config,
+            dual_op_config,
+        }
+    }
+}
+// ANCHOR_END: field-chip-impl
+
+// ANCHOR: field-instructions-impl
+impl<F: FieldExt> FieldInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Option<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let config = self.config();
+
+        let mut num = None;
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                let cell = region.assign_advice(
+                    || "private input",
+                    config.advice[0],
+                    0,
+                    || value.ok_or(Error::SynthesisError),
+                )?;
+                num = Some(Number { cell, value });
+                Ok(())
+            },
+        )?;
+        Ok(num.unwrap())
+    }
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let config = self.config();
+
+        let mut num = None;
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                let cell =
+                    region.assign_fixed(|| "constant", config.constant, 0, || Ok(constant))?;
+                num = Some(Number {
+                    cell,
+                    value: Some(constant),
+                });
+                Ok(())
+            },
+        )?;
+        Ok(num.unwrap())
+    }
+
+    /// Returns `d = a - b`.
+    fn subtract(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_subtract(layouter.namespace(|| "a - b"), a, b)
+    }
+
+    fn negate(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let zero = self.load_constant(layouter.namespace(|| "zero"), F::zero())?;
+        self.do_subtract(layouter.namespace(|| "0 - a"), zero, a)
+    }
+
+    fn subtract_chain(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_subtract_inline(layouter.namespace(|| "subtract chain"), inputs)
+    }
+
+    fn sum(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        if inputs.is_empty() {
+            return self.load_constant(layouter.namespace(|| "zero"), F::zero());
+        }
+        self.do_sum(layouter.namespace(|| "sum"), inputs)
+    }
+
+    fn dot(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &[<Self as FieldInstructions<F>>::Num],
+        b: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        if a.len() != b.len() {
+            return Err(Error::SynthesisError);
+        }
+        if a.is_empty() {
+            return self.load_constant(layouter.namespace(|| "zero"), F::zero());
+        }
+        self.do_dot(layouter.namespace(|| "dot"), a, b)
+    }
+
+    /// Returns `d = a + b`.
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_add(layouter.namespace(|| "a + b"), a, b)
+    }
+
+    /// Returns `d = a * b`.
+    fn multiply(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_mul(layouter.namespace(|| "a * b"), a, b)
+    }
+
+    /// Returns `d = a * a`.
+    fn square(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_square(layouter.namespace(|| "a * a"), a)
+    }
+
+    fn sbox(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let x2 = self.square(layouter, x.clone())?;
+        let x4 = self.square(layouter, x2)?;
+        self.multiply(layouter, x4, x)
+    }
+
+    fn pow_fixed(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        base: <Self as FieldInstructions<F>>::Num,
+        exp: u64,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        if exp == 0 {
+            return self.load_constant(layouter.namespace(|| "one"), F::one());
+        }
+
+        // The top bit of `exp` is always set, so start from `base` and walk the
+        // remaining bits from the most significant down.
+        let bits = 64 - exp.leading_zeros();
+        let mut acc = base.clone();
+        for i in (0..bits - 1).rev() {
+            acc = self.square(layouter, acc)?;
+            if (exp >> i) & 1 == 1 {
+                acc = self.multiply(layouter, acc, base.clone())?;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Returns `d = a / b`.
+    fn divide(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_div(layouter.namespace(|| "a / b"), a, b)
+    }
+
+    fn dual_op_select(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+        sel: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_dual_op_select(layouter.namespace(|| "sel ? a + b : a - b"), a, b, sel)
+    }
+
+    fn assert_on_line(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+        y: <Self as FieldInstructions<F>>::Num,
+        m: F,
+        b: F,
+    ) -> Result<(), Error> {
+        self.do_assert_on_line(layouter.namespace(|| "y = m * x + b"), x, y, m, b)
+    }
+
+    fn assert_quadratic(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<(), Error> {
+        self.do_assert_quadratic(layouter.namespace(|| "a * x^2 + b * x + c = 0"), x, a, b, c)
+    }
+
+    fn mul_by_constant(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        c: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_mul_by_constant(layouter.namespace(|| "c * a"), a, c)
+    }
+
+    fn bit_reverse(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error> {
+        if !values.len().is_power_of_two() {
+            return Err(Error::SynthesisError);
+        }
+        let config = self.config();
+
+        let bits = values.len().trailing_zeros();
+        let reverse = |i: usize| (0..bits).fold(0, |acc, j| (acc << 1) | ((i >> j) & 1));
+
+        // No gate is needed: each output is a copy of the input at the reversed
+        // index, enforced purely by equality constraints.
+        let mut out = vec![];
+        layouter.assign_region(
+            || "bit reverse",
+            |mut region: Region<'_, F>| {
+                out.clear();
+                for i in 0..values.len() {
+                    let input = &values[reverse(i)];
+                    let cell = region.assign_advice(
+                        || format!("out[{}]", i),
+                        config.advice[0],
+                        i,
+                        || input.value.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(input.cell, cell)?;
+                    out.push(Number {
+                        cell,
+                        value: input.value,
+                    });
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(out)
+    }
+
+    fn assert_shuffle(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        input: &[<Self as FieldInstructions<F>>::Num],
+        output: &[<Self as FieldInstructions<F>>::Num],
+        perm: &[usize],
+    ) -> Result<(), Error> {
+        // `perm` must be a permutation of `0..input.len()`.
+        let mut seen = vec![false; input.len()];
+        if output.len() != input.len() || perm.len() != input.len() {
+            return Err(Error::SynthesisError);
+        }
+        for &i in perm {
+            if i >= input.len() || seen[i] {
+                return Err(Error::SynthesisError);
+            }
+            seen[i] = true;
+        }
+
+        // As with `bit_reverse`, equality constraints alone enforce the shuffle.
+        layouter.assign_region(
+            || "shuffle",
+            |mut region: Region<'_, F>| {
+                for (out, &i) in output.iter().zip(perm) {
+                    region.constrain_equal(input[i].cell, out.cell)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn fir2(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x_prev: <Self as FieldInstructions<F>>::Num,
+        x_cur: <Self as FieldInstructions<F>>::Num,
+        c0: F,
+        c1: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        self.do_linear_combination(
+            layouter.namespace(|| "c0 * x_cur + c1 * x_prev"),
+            x_cur,
+            x_prev,
+            c0,
+            c1,
+        )
+    }
+
+    fn fir_stream(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[<Self as FieldInstructions<F>>::Num],
+        c0: F,
+        c1: F,
+    ) -> Result<Vec<<Self as FieldInstructions<F>>::Num>, Error> {
+        let mut out = Vec::with_capacity(values.len());
+        for (i, x_cur) in values.iter().enumerate() {
+            let y = match i {
+                // The delayed sample starts at zero, so the first tap is dropped
+                // by giving it a zero coefficient.
+                0 => self.fir2(layouter, x_cur.clone(), x_cur.clone(), c0, F::zero())?,
+                _ => self.fir2(layouter, values[i - 1].clone(), x_cur.clone(), c0, c1)?,
+            };
+            out.push(y);
+        }
+        Ok(out)
+    }
+
+    fn reconstruct_secret(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        shares: &[(F, <Self as FieldInstructions<F>>::Num)],
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        // The Lagrange coefficients at zero only depend on the public share
+        // positions, so they are computed outside the circuit:
+        //
+        //     lambda_i = prod_{j != i} x_j / (x_j - x_i)
+        let coeffs = shares
+            .iter()
+            .enumerate()
+            .map(|(i, (x_i, _))| {
+                shares
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .try_fold(F::one(), |acc, (_, (x_j, _))| {
+                        // Repeated share positions have no inverse.
+                        Option::<F>::from((*x_j - *x_i).invert()).map(|inv| acc * *x_j * inv)
+                    })
+                    .ok_or(Error::SynthesisError)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ((_, first), rest) = shares.split_first().ok_or(Error::SynthesisError)?;
+        let mut acc = self.do_linear_combination(
+            layouter.namespace(|| "lambda_0 * y_0"),
+            first.clone(),
+            first.clone(),
+            coeffs[0],
+            F::zero(),
+        )?;
+        for (i, (_, y)) in rest.iter().enumerate() {
+            acc = self.do_linear_combination(
+                layouter.namespace(|| format!("acc + lambda_{} * y_{}", i + 1, i + 1)),
+                acc,
+                y.clone(),
+                F::one(),
+                coeffs[i + 1],
+            )?;
+        }
+        Ok(acc)
+    }
+
+    fn assert_poly_eval(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        coeffs: &[<Self as FieldInstructions<F>>::Num],
+        z: F,
+        claimed: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error> {
+        // Horner's rule: starting from the leading coefficient, each step is
+        // `acc = z * acc + c_i`, which is exactly one linear combination gate.
+        let (leading, rest) = coeffs.split_last().ok_or(Error::SynthesisError)?;
+        let mut acc = leading.clone();
+        for (i, c) in rest.iter().enumerate().rev() {
+            acc = self.do_linear_combination(
+                layouter.namespace(|| format!("z * acc + c_{}", i)),
+                acc,
+                c.clone(),
+                z,
+                F::one(),
+            )?;
+        }
+
+        layouter.assign_region(
+            || "assert poly eval",
+            |mut region: Region<'_, F>| region.constrain_equal(acc.cell, claimed.cell),
+        )
+    }
+
+    fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: <Self as FieldInstructions<F>>::Num,
+        b: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assert equal",
+            |mut region: Region<'_, F>| region.constrain_equal(a.cell, b.cell),
+        )
+    }
+
+    fn assert_checksum(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        data: &[<Self as FieldInstructions<F>>::Num],
+        expected: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<(), Error> {
+        let total = self.sum(layouter, data)?;
+        self.assert_equal(layouter.namespace(|| "checksum"), total, expected)
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.constrain_instance(num.cell, config.instance, row)
+    }
+
+    fn expose_public_batch(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[(<Self as FieldInstructions<F>>::Num, usize)],
+    ) -> Result<(), Error> {
+        for (num, row) in values {
+            self.expose_public(
+                layouter.namespace(|| format!("expose row {}", row)),
+                num.clone(),
+                *row,
+            )?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: field-instructions-impl
+
+// ANCHOR: circuit
+/// The full circuit implementation.
+///
+/// In this struct we store the private input variables. We use `Option<F>` because
+/// they won't have any value during key generation. During proving, if any of these
+/// were `None` we would get an error.
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    // Since we are using a single chip for everything, we can just reuse its config.
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        // We create the two advice columns that FieldChip uses for I/O.
+        let advice = [meta.advice_column(), meta.advice_column()];
+
+        // We also need an instance column to store public inputs.
+        let instance = meta.instance_column();
+
+        FieldChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        // Load our private values into the circuit.
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        // Use `subtract` to get `d = a - b`.
+        let d = field_chip.subtract(&mut layouter, a.clone(), b)?;
+
+        // Chain the difference into `add` to get `e = d + a`.
+        let e = field_chip.add(&mut layouter, d.clone(), a)?;
+
+        // Expose the difference and the chained result as public inputs.
+        field_chip.expose_public_batch(layouter.namespace(|| "expose d and e"), &[(d, 0), (e, 1)])
+    }
+}
+// ANCHOR_END: circuit
+
+// ANCHOR: mul-circuit
+/// A circuit exposing the product of its two private inputs.
+#[derive(Default)]
+pub struct MulCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for MulCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        let c = field_chip.multiply(&mut layouter, a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+    }
+}
+// ANCHOR_END: mul-circuit
+
+// ANCHOR: square-circuit
+/// A circuit exposing the square of its private input.
+#[derive(Default)]
+pub struct SquareCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    /// Computes `multiply(a, a)` instead, as a baseline for the layout of
+    /// `square`.
+    pub via_multiply: bool,
+}
+
+impl<F: FieldExt> Circuit<F> for SquareCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: None,
+            via_multiply: self.via_multiply,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+
+        let c = if self.via_multiply {
+            field_chip.multiply(&mut layouter, a.clone(), a)?
+        } else {
+            field_chip.square(&mut layouter, a)?
+        };
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+    }
+}
+// ANCHOR_END: square-circuit
+
+// ANCHOR: pow-circuit
+/// A circuit exposing its private input raised to the fixed exponent `exp`.
+#[derive(Default)]
+pub struct PowCircuit<F: FieldExt> {
+    pub base: Option<F>,
+    pub exp: u64,
+}
+
+impl<F: FieldExt> Circuit<F> for PowCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            base: None,
+            exp: self.exp,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let base = field_chip.load_private(layouter.namespace(|| "load base"), self.base)?;
+
+        let out = field_chip.pow_fixed(&mut layouter, base, self.exp)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: pow-circuit
+
+// ANCHOR: sbox-circuit
+/// A circuit exposing the S-box `x^5` of its private input.
+#[derive(Default)]
+pub struct SboxCircuit<F: FieldExt> {
+    pub x: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for SboxCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let out = field_chip.sbox(&mut layouter, x)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: sbox-circuit
+
+// ANCHOR: mul-by-constant-circuit
+/// A circuit exposing its private input scaled by the constant `c`.
+#[derive(Default)]
+pub struct MulByConstantCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub c: F,
+}
+
+impl<F: FieldExt> Circuit<F> for MulByConstantCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { a: None, c: self.c }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+
+        let out = field_chip.mul_by_constant(&mut layouter, a, self.c)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: mul-by-constant-circuit
+
+// ANCHOR: subtract-chain-circuit
+/// A circuit exposing the running difference of its private inputs.
+#[derive(Default)]
+pub struct SubtractChainCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for SubtractChainCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(layouter.namespace(|| format!("load {}", i)), *value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let out = field_chip.subtract_chain(&mut layouter, &values)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: subtract-chain-circuit
+
+// ANCHOR: chain-circuit
+/// A circuit exposing the left fold `((x0 - x1) - x2) - ... - xN`, with each
+/// subtraction laid out in its own region.
+pub struct ChainCircuit<F: FieldExt, const N: usize> {
+    pub first: Option<F>,
+    pub rest: [Option<F>; N],
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for ChainCircuit<F, N> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            first: None,
+            rest: [None; N],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut acc = field_chip.load_private(layouter.namespace(|| "load x0"), self.first)?;
+        for (i, value) in self.rest.iter().enumerate() {
+            let x = field_chip
+                .load_private(layouter.namespace(|| format!("load x{}", i + 1)), *value)?;
+            acc = field_chip.subtract(&mut layouter, acc, x)?;
+        }
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), acc, 0)
+    }
+}
+// ANCHOR_END: chain-circuit
+
+// ANCHOR: negate-circuit
+/// A circuit exposing `-a`, and `-(-a)` to check that negation round-trips.
+#[derive(Default)]
+pub struct NegateCircuit<F: FieldExt> {
+    pub a: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for NegateCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+
+        let neg = field_chip.negate(&mut layouter, a)?;
+        let neg_neg = field_chip.negate(&mut layouter, neg.clone())?;
+
+        field_chip.expose_public_batch(
+            layouter.namespace(|| "expose -a and -(-a)"),
+            &[(neg, 0), (neg_neg, 1)],
+        )
+    }
+}
+// ANCHOR_END: negate-circuit
+
+// ANCHOR: sum-circuit
+/// A circuit exposing the sum of its private inputs.
+#[derive(Default)]
+pub struct SumCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for SumCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(layouter.namespace(|| format!("load {}", i)), *value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let out = field_chip.sum(&mut layouter, &values)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: sum-circuit
+
+// ANCHOR: dot-circuit
+/// A circuit exposing the dot product of its two private vectors.
+#[derive(Default)]
+pub struct DotCircuit<F: FieldExt> {
+    pub a: Vec<Option<F>>,
+    pub b: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for DotCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: vec![None; self.a.len()],
+            b: vec![None; self.b.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut load = |name: &str, values: &[Option<F>]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    field_chip.load_private(
+                        layouter.namespace(|| format!("load {}[{}]", name, i)),
+                        *value,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let a = load("a", &self.a)?;
+        let b = load("b", &self.b)?;
+
+        let out = field_chip.dot(&mut layouter, &a, &b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: dot-circuit
+
+// ANCHOR: div-circuit
+/// A circuit exposing the quotient of its two private inputs.
+#[derive(Default)]
+pub struct DivCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for DivCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        let c = field_chip.divide(&mut layouter, a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+    }
+}
+// ANCHOR_END: div-circuit
+
+// ANCHOR: on-line-circuit
+/// A circuit proving that the private point `(x, y)` lies on the line `y = m * x + b`.
+#[derive(Default)]
+pub struct OnLineCircuit<F: FieldExt> {
+    pub x: Option<F>,
+    pub y: Option<F>,
+    pub m: F,
+    pub b: F,
+}
+
+impl<F: FieldExt> Circuit<F> for OnLineCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: None,
+            y: None,
+            m: self.m,
+            b: self.b,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let y = field_chip.load_private(layouter.namespace(|| "load y"), self.y)?;
+
+        field_chip.assert_on_line(&mut layouter, x, y, self.m, self.b)
+    }
+}
+// ANCHOR_END: on-line-circuit
+
+// ANCHOR: quadratic-circuit
+/// A circuit proving that the private `x` is a root of `a * x^2 + b * x + c`.
+#[derive(Default)]
+pub struct QuadraticCircuit<F: FieldExt> {
+    pub x: Option<F>,
+    pub a: F,
+    pub b: F,
+    pub c: F,
+}
+
+impl<F: FieldExt> Circuit<F> for QuadraticCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: None,
+            a: self.a,
+            b: self.b,
+            c: self.c,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        field_chip.assert_quadratic(&mut layouter, x, self.a, self.b, self.c)
+    }
+}
+// ANCHOR_END: quadratic-circuit
+
+// ANCHOR: bit-reverse-circuit
+/// A circuit exposing its private inputs in bit-reversed order.
+#[derive(Default)]
+pub struct BitReverseCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for BitReverseCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(layouter.namespace(|| format!("load {}", i)), *value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let reversed = field_chip.bit_reverse(&mut layouter, &values)?;
+
+        for (row, num) in reversed.into_iter().enumerate() {
+            field_chip.expose_public(layouter.namespace(|| format!("expose {}", row)), num, row)?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: bit-reverse-circuit
+
+// ANCHOR: shuffle-circuit
+/// A circuit proving that its private `output` is its private `input` shuffled
+/// by the public permutation `perm`.
+#[derive(Default)]
+pub struct ShuffleCircuit<F: FieldExt> {
+    pub input: Vec<Option<F>>,
+    pub output: Vec<Option<F>>,
+    pub perm: Vec<usize>,
+}
+
+impl<F: FieldExt> Circuit<F> for ShuffleCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            input: vec![None; self.input.len()],
+            output: vec![None; self.output.len()],
+            perm: self.perm.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let mut load = |name: &str, values: &[Option<F>]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    field_chip.load_private(
+                        layouter.namespace(|| format!("load {}[{}]", name, i)),
+                        *value,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let input = load("input", &self.input)?;
+        let output = load("output", &self.output)?;
+
+        field_chip.assert_shuffle(&mut layouter, &input, &output, &self.perm)
+    }
+}
+// ANCHOR_END: shuffle-circuit
+
+// ANCHOR: assert-equal-circuit
+/// A circuit asserting that its two private inputs are equal.
+#[derive(Default)]
+pub struct AssertEqualCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for AssertEqualCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        field_chip.assert_equal(layouter.namespace(|| "a == b"), a, b)
+    }
+}
+// ANCHOR_END: assert-equal-circuit
+
+// ANCHOR: checksum-circuit
+/// A circuit proving that its private data sums to its private checksum.
+#[derive(Default)]
+pub struct ChecksumCircuit<F: FieldExt> {
+    pub data: Vec<Option<F>>,
+    pub expected: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for ChecksumCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            data: vec![None; self.data.len()],
+            expected: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let data = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(layouter.namespace(|| format!("load {}", i)), *value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected =
+            field_chip.load_private(layouter.namespace(|| "load expected"), self.expected)?;
+
+        field_chip.assert_checksum(&mut layouter, &data, expected)
+    }
+}
+// ANCHOR_END: checksum-circuit
+
+// ANCHOR: fir-circuit
+/// A circuit running its private inputs through a 2-tap FIR filter and exposing
+/// every filtered sample.
+#[derive(Default)]
+pub struct FirCircuit<F: FieldExt> {
+    pub values: Vec<Option<F>>,
+    pub c0: F,
+    pub c1: F,
+}
+
+impl<F: FieldExt> Circuit<F> for FirCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+            c0: self.c0,
+            c1: self.c1,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                field_chip.load_private(layouter.namespace(|| format!("load {}", i)), *value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let filtered = field_chip.fir_stream(&mut layouter, &values, self.c0, self.c1)?;
+
+        for (row, num) in filtered.into_iter().enumerate() {
+            field_chip.expose_public(layouter.namespace(|| format!("expose {}", row)), num, row)?;
+        }
+        Ok(())
+    }
+}
+// ANCHOR_END: fir-circuit
+
+// ANCHOR: secret-share-circuit
+/// A circuit reconstructing a secret from private shares at public positions,
+/// exposing the secret.
+#[derive(Default)]
+pub struct SecretShareCircuit<F: FieldExt> {
+    pub shares: Vec<(F, Option<F>)>,
+}
+
+impl<F: FieldExt> Circuit<F> for SecretShareCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            shares: self.shares.iter().map(|(x, _)| (*x, None)).collect(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let shares = self
+            .shares
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| {
+                field_chip
+                    .load_private(layouter.namespace(|| format!("load share {}", i)), *y)
+                    .map(|y| (*x, y))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let secret = field_chip.reconstruct_secret(&mut layouter, &shares)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose secret"), secret, 0)
+    }
+}
+// ANCHOR_END: secret-share-circuit
+
+// ANCHOR: dual-op-circuit
+/// A circuit exposing `a + b` or `a - b` depending on the private selector `sel`.
+#[derive(Default)]
+pub struct DualOpCircuit<F: FieldExt> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+    pub sel: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for DualOpCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+        let sel = field_chip.load_private(layouter.namespace(|| "load sel"), self.sel)?;
+
+        let out = field_chip.dual_op_select(&mut layouter, a, b, sel)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
+    }
+}
+// ANCHOR_END: dual-op-circuit
+
+// ANCHOR: poly-eval-circuit
+/// A circuit proving that a private polynomial evaluates to a private claim at
+/// the fixed point `z`.
+#[derive(Default)]
+pub struct PolyEvalCircuit<F: FieldExt> {
+    pub coeffs: Vec<Option<F>>,
+    pub claimed: Option<F>,
+    pub z: F,
+}
+
+impl<F: FieldExt> Circuit<F> for PolyEvalCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            coeffs: vec![None; self.coeffs.len()],
+            claimed: None,
+            z: self.z,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        let coeffs = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                field_chip.load_private(layouter.namespace(|| format!("load c_{}", i)), *c)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let claimed =
+            field_chip.load_private(layouter.namespace(|| "load claimed"), self.claimed)?;
+
+        field_chip.assert_poly_eval(&mut layouter, &coeffs, self.z, claimed)
+    }
+}
+// ANCHOR_END: poly-eval-circuit
+
+// ANCHOR: prove-and-verify
+/// Generates the parameters and proving key for `circuit` over the IPA
+/// commitment scheme, with `2^k` rows.
+pub fn keygen<C: Circuit<pasta::Fp>>(
+    k: u32,
+    circuit: &C,
+) -> Result<(Params<EqAffine>, ProvingKey<EqAffine>), Error> {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+    Ok((params, pk))
+}
+
+/// Creates a proof that `circuit` is satisfied with `public_inputs`.
+pub fn prove<C: Circuit<pasta::Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &C,
+    public_inputs: &[pasta::Fp],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        std::slice::from_ref(circuit),
+        &[&[public_inputs]],
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Returns whether `proof` verifies against `public_inputs`.
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public_inputs: &[pasta::Fp],
+) -> bool {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[&[public_inputs]], &mut transcript).is_ok()
+}
+
+/// Runs the full proving pipeline for `circuit` over the IPA commitment scheme:
+/// key generation, proof creation and verification against `public_inputs`.
+///
+/// Returns `Ok(false)` if the proof does not verify, and an error if keygen or
+/// proving fails.
+pub fn prove_and_verify<C: Circuit<pasta::Fp>>(
+    k: u32,
+    circuit: C,
+    public_inputs: &[pasta::Fp],
+) -> Result<bool, Error> {
+    let (params, pk) = keygen(k, &circuit)?;
+    let proof = prove(&params, &pk, &circuit, public_inputs)?;
+    Ok(verify(&params, pk.get_vk(), &proof, public_inputs))
+}
+// ANCHOR_END: prove-and-verify
+
+// ANCHOR: build-circuit
+/// Builds a subtraction chain sized to fill a fair fraction of the `2^k` rows,
+/// along with its public inputs, for timing the proving pipeline as `k` grows.
+/// Panics if `k < 4`.
+pub fn build_circuit(k: u32) -> (SubtractChainCircuit<pasta::Fp>, Vec<pasta::Fp>) {
+    assert!(k >= 4, "build_circuit requires k >= 4");
+
+    let values: Vec<pasta::Fp> = (0..(1 << (k - 3)) + 1)
+        .map(|i| pasta::Fp::from_u64(i + 1))
+        .collect();
+    let out = values[1..]
+        .iter()
+        .fold(values[0], |acc, &value| acc - value);
+
+    let circuit = SubtractChainCircuit {
+        values: values.into_iter().map(Some).collect(),
+    };
+    (circuit, vec![out])
+}
+// ANCHOR_END: build-circuit
@@ -1,404 +1,521 @@
-use std::marker::PhantomData;
-
-use halo2::{
-    arithmetic::FieldExt,
-    circuit::{Cell, Chip, Layouter, Region, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
-    poly::Rotation,
+use halo2::arithmetic::FieldExt;
+
+use subtract::{
+    build_circuit, prove_and_verify, AssertEqualCircuit, BitReverseCircuit, ChainCircuit,
+    ChecksumCircuit, DivCircuit, DotCircuit, DualOpCircuit, FirCircuit, MulByConstantCircuit,
+    MulCircuit, MyCircuit, NegateCircuit, OnLineCircuit, PolyEvalCircuit, PowCircuit,
+    QuadraticCircuit, SboxCircuit, SecretShareCircuit, ShuffleCircuit, SquareCircuit,
+    SubtractChainCircuit, SumCircuit,
 };
 
-// ANCHOR: field-instructions
-/// A variable representing a number.
-#[derive(Clone)]
-struct Number<F: FieldExt> {
-    cell: Cell,
-    value: Option<F>,
-}
+mod test_utils;
+use test_utils::{assert_fails_gate, count_layout};
 
-trait FieldInstructions<F: FieldExt>: SubtractInstructions<F> {
-    /// Variable representing a number.
-    type Num;
-
-    /// Loads a number into the circuit as a private input.
-    fn load_private(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Option<F>,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
-
-    /// Returns `d = a - b`.
-    fn subtract(
-        &self,
-        layouter: &mut impl Layouter<F>,
-        a: <Self as FieldInstructions<F>>::Num,
-        b: <Self as FieldInstructions<F>>::Num,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
-
-    /// Exposes a number as a public input to the circuit.
-    fn expose_public(
-        &self,
-        layouter: impl Layouter<F>,
-        num: <Self as FieldInstructions<F>>::Num,
-        row: usize,
-    ) -> Result<(), Error>;
-}
-// ANCHOR_END: field-instructions
-
-// ANCHOR: subtract-instructions
-trait SubtractInstructions<F: FieldExt>: Chip<F> {
-    /// Variable representing a number.
-    type Num;
-
-    /// Returns `c = a - b`.
-    fn do_subtract(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error>;
-}
-// ANCHOR_END: subtract-instructions
-
-// ANCHOR: field-config
-// The top-level config that provides all necessary columns and permutations
-// for the other configs.
-#[derive(Clone, Debug)]
-struct FieldConfig {
-    /// For this chip, we will use two advice columns to implement our instructions.
-    /// These are also the columns through which we communicate with other parts of
-    /// the circuit.
-    advice: [Column<Advice>; 2],
-
-    /// Public inputs
-    instance: Column<Instance>,
-
-    subtract_config: SubtractConfig,
-}
-// ANCHOR END: field-config
+#[allow(clippy::many_single_char_names)]
+fn main() {
+    use halo2::{dev::MockProver, pasta::Fp};
 
-// ANCHOR: subtract-config
-#[derive(Clone, Debug)]
-struct SubtractConfig {
-    advice: [Column<Advice>; 2],
-    s_subtract: Selector,
-}
-// ANCHOR_END: subtract-config
+    // ANCHOR: test-circuit
+    // The number of rows in our circuit cannot exceed 2^k. Since our example
+    // circuit is very small, we can pick a very small value here.
+    let k = 4;
 
-// ANCHOR: field-chip
-/// The top-level chip that will implement the `FieldInstructions`.
-struct FieldChip<F: FieldExt> {
-    config: FieldConfig,
-    _marker: PhantomData<F>,
-}
-// ANCHOR_END: field-chip
+    // Prepare the private and public inputs to the circuit!
+    let a = Fp::rand();
+    let b = Fp::rand();
+    let d = a - b;
+    let e = d + a;
 
-// ANCHOR: subtract-chip
-struct SubtractChip<F: FieldExt> {
-    config: SubtractConfig,
-    _marker: PhantomData<F>,
-}
-// ANCHOR END: subtract-chip
+    // Instantiate the circuit with the private inputs.
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
 
-// ANCHOR: subtract-chip-trait-impl
-impl<F: FieldExt> Chip<F> for SubtractChip<F> {
-    type Config = SubtractConfig;
-    type Loaded = ();
+    // Arrange the public inputs. We expose the difference in row 0 and the
+    // chained result in row 1 of the instance column.
+    let mut public_inputs = vec![d, e];
 
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
+    // Given the correct public input, our circuit will verify.
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
-// ANCHOR END: subtract-chip-trait-impl
-
-// ANCHOR: subtract-chip-impl
-impl<F: FieldExt> SubtractChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
-    }
+    // If we try some other public input, the proof will fail!
+    public_inputs[0] += Fp::one();
+    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    assert!(prover.verify().is_err());
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 2],
-    ) -> <Self as Chip<F>>::Config {
-        let s_subtract = meta.selector();
+    // Supplying only the first public input leaves row 1 unset, so it fails too.
+    let prover = MockProver::run(k, &circuit, vec![vec![d]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-circuit
 
-        // Define our subtraction gate!
-        meta.create_gate("subtract", |meta| {
-            let lhs = meta.query_advice(advice[0], Rotation::cur());
-            let rhs = meta.query_advice(advice[1], Rotation::cur());
-            let out = meta.query_advice(advice[0], Rotation::next());
-            let s_subtract = meta.query_selector(s_subtract);
+    // ANCHOR: test-prove-and-verify
+    // A real proof for the same circuit verifies against the correct public
+    // input, and not against a wrong one.
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    assert!(prove_and_verify(k, circuit, &[d, e]).unwrap());
+
+    let circuit = MyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    assert!(!prove_and_verify(k, circuit, &[d, e + Fp::one()]).unwrap());
 
-            vec![s_subtract * (lhs - rhs - out)]
-        });
+    let (circuit, public_inputs) = build_circuit(k);
+    assert!(prove_and_verify(k, circuit, &public_inputs).unwrap());
+    // ANCHOR_END: test-prove-and-verify
 
-        SubtractConfig { advice, s_subtract }
-    }
-}
-// ANCHOR END: subtract-chip-impl
-
-// ANCHOR: subtract-instructions-impl
-impl<F: FieldExt> SubtractInstructions<F> for FieldChip<F> {
-    type Num = Number<F>;
-    fn do_subtract(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error> {
-        let config = self.config().subtract_config.clone();
-
-        let subtract_chip = SubtractChip::<F>::construct(config, ());
-        subtract_chip.do_subtract(layouter, a, b)
-    }
-}
+    // ANCHOR: test-mul
+    let a = Fp::rand();
+    let b = Fp::rand();
+    let circuit = MulCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![a * b]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-impl<F: FieldExt> SubtractInstructions<F> for SubtractChip<F> {
-    type Num = Number<F>;
-
-    fn do_subtract(
-        &self,
-        mut layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error> {
-        let config = self.config();
-
-        let mut out = None;
-        layouter.assign_region(
-            || "subtract",
-            |mut region: Region<'_, F>| {
-                // We only want to use a single subtraction gate in this region,
-                // so we enable it at region offset 0; this means it will constrain
-                // cells at offsets 0 and 1.
-                config.s_subtract.enable(&mut region, 0)?;
-
-                // The inputs we've been given could be located anywhere in the circuit,
-                // but we can only rely on relative offsets inside this region. So we
-                // assign new cells inside the region and constrain them to have the
-                // same values as the inputs.
-                let lhs = region.assign_advice(
-                    || "lhs",
-                    config.advice[0],
-                    0,
-                    || a.value.ok_or(Error::SynthesisError),
-                )?;
-                let rhs = region.assign_advice(
-                    || "rhs",
-                    config.advice[1],
-                    0,
-                    || b.value.ok_or(Error::SynthesisError),
-                )?;
-                region.constrain_equal(a.cell, lhs)?;
-                region.constrain_equal(b.cell, rhs)?;
-
-                // Now we can assign the subtraction result into the output position.
-                let value = a.value.and_then(|a| b.value.map(|b| a - b));
-                let cell = region.assign_advice(
-                    || "lhs - rhs",
-                    config.advice[0],
-                    1,
-                    || value.ok_or(Error::SynthesisError),
-                )?;
-
-                // Finally, we return a variable representing the output,
-                // to be used in another part of the circuit.
-                out = Some(Number { cell, value });
-                Ok(())
-            },
-        )?;
-
-        Ok(out.unwrap())
-    }
-}
-// ANCHOR END: subtract-instructions-impl
+    let prover = MockProver::run(k, &circuit, vec![vec![a * b + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-mul
 
-// ANCHOR: field-chip-trait-impl
-impl<F: FieldExt> Chip<F> for FieldChip<F> {
-    type Config = FieldConfig;
-    type Loaded = ();
+    // ANCHOR: test-mul-by-constant
+    let a = Fp::rand();
+    let circuit = MulByConstantCircuit {
+        a: Some(a),
+        c: Fp::from_u64(7),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![a * Fp::from_u64(7)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
+    let prover = MockProver::run(k, &circuit, vec![vec![a]]).unwrap();
+    assert!(prover.verify().is_err());
 
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
-// ANCHOR_END: field-chip-trait-impl
-
-// ANCHOR: field-chip-impl
-impl<F: FieldExt> FieldChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
-    }
+    // Scaling by zero always yields zero.
+    let circuit = MulByConstantCircuit {
+        a: Some(a),
+        c: Fp::zero(),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    // ANCHOR_END: test-mul-by-constant
+
+    // ANCHOR: test-subtract-chain
+    // 100 - 1 - 2 - 3 - 4 = 90, with the four subtractions sharing five rows.
+    let circuit = SubtractChainCircuit {
+        values: [100, 1, 2, 3, 4]
+            .iter()
+            .map(|&v| Some(Fp::from_u64(v)))
+            .collect(),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(90)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 2],
-        instance: Column<Instance>,
-    ) -> <Self as Chip<F>>::Config {
-        let subtract_config = SubtractChip::configure(meta, advice);
-
-        meta.enable_equality(instance.into());
-        for column in &advice {
-            meta.enable_equality((*column).into());
-        }
-
-        FieldConfig {
-            advice,
-            instance,
-            subtract_config,
-        }
-    }
-}
-// ANCHOR_END: field-chip-impl
-
-// ANCHOR: field-instructions-impl
-impl<F: FieldExt> FieldInstructions<F> for FieldChip<F> {
-    type Num = Number<F>;
-
-    fn load_private(
-        &self,
-        mut layouter: impl Layouter<F>,
-        value: Option<F>,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
-        let config = self.config();
-
-        let mut num = None;
-        layouter.assign_region(
-            || "load private",
-            |mut region| {
-                let cell = region.assign_advice(
-                    || "private input",
-                    config.advice[0],
-                    0,
-                    || value.ok_or(Error::SynthesisError),
-                )?;
-                num = Some(Number { cell, value });
-                Ok(())
-            },
-        )?;
-        Ok(num.unwrap())
-    }
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(91)]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-subtract-chain
+
+    // ANCHOR: test-chain
+    let first = Fp::rand();
+    let rest: [Fp; 3] = [Fp::rand(), Fp::rand(), Fp::rand()];
+    let out = rest.iter().fold(first, |acc, &x| acc - x);
+    let circuit = ChainCircuit::<Fp, 3> {
+        first: Some(first),
+        rest: rest.map(Some),
+    };
+    let prover = MockProver::run(6, &circuit, vec![vec![out]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    /// Returns `d = a - b`.
-    fn subtract(
-        &self,
-        layouter: &mut impl Layouter<F>,
-        a: <Self as FieldInstructions<F>>::Num,
-        b: <Self as FieldInstructions<F>>::Num,
-    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
-        self.do_subtract(layouter.namespace(|| "a - b"), a, b)
-    }
+    let prover = MockProver::run(6, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
 
-    fn expose_public(
-        &self,
-        mut layouter: impl Layouter<F>,
-        num: <Self as FieldInstructions<F>>::Num,
-        row: usize,
-    ) -> Result<(), Error> {
-        let config = self.config();
+    let first = Fp::rand();
+    let rest: [Fp; 10] = [(); 10].map(|_| Fp::rand());
+    let out = rest.iter().fold(first, |acc, &x| acc - x);
+    let circuit = ChainCircuit::<Fp, 10> {
+        first: Some(first),
+        rest: rest.map(Some),
+    };
+    let prover = MockProver::run(6, &circuit, vec![vec![out]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-        layouter.constrain_instance(num.cell, config.instance, row)
-    }
-}
-// ANCHOR_END: field-instructions-impl
-
-// ANCHOR: circuit
-/// The full circuit implementation.
-///
-/// In this struct we store the private input variables. We use `Option<F>` because
-/// they won't have any value during key generation. During proving, if any of these
-/// were `None` we would get an error.
-#[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    a: Option<F>,
-    b: Option<F>,
-}
+    let prover = MockProver::run(6, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-chain
 
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-    // Since we are using a single chip for everything, we can just reuse its config.
-    type Config = FieldConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+    // ANCHOR: test-negate
+    let a = Fp::rand();
+    let circuit = NegateCircuit { a: Some(a) };
+    let prover = MockProver::run(k, &circuit, vec![vec![-a, a]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
+    let prover = MockProver::run(k, &circuit, vec![vec![a, a]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-negate
+
+    // ANCHOR: test-sum
+    // 1 + 2 + 3 + 4 + 5 = 15.
+    let circuit = SumCircuit {
+        values: (1..=5).map(|v| Some(Fp::from_u64(v))).collect(),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(15)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(14)]]).unwrap();
+    assert!(prover.verify().is_err());
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        // We create the two advice columns that FieldChip uses for I/O.
-        let advice = [meta.advice_column(), meta.advice_column()];
+    // The empty sum is a constant zero.
+    let circuit = SumCircuit { values: vec![] };
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::zero()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-        // We also need an instance column to store public inputs.
-        let instance = meta.instance_column();
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-sum
 
-        FieldChip::configure(meta, advice, instance)
+    // ANCHOR: test-square
+    let a = Fp::rand();
+    for via_multiply in [false, true] {
+        let circuit = SquareCircuit {
+            a: Some(a),
+            via_multiply,
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![a * a]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![a * a + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
     }
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        let field_chip = FieldChip::<F>::construct(config, ());
+    // The square region assigns two cells and copies its input once, where
+    // `multiply(a, a)` assigns three cells and copies its input twice.
+    let square = count_layout(&SquareCircuit {
+        a: Some(a),
+        via_multiply: false,
+    })
+    .unwrap();
+    let multiply = count_layout(&SquareCircuit {
+        a: Some(a),
+        via_multiply: true,
+    })
+    .unwrap();
+    assert_eq!(square.advice_cells + 1, multiply.advice_cells);
+    assert_eq!(square.copies + 1, multiply.copies);
+    // ANCHOR_END: test-square
+
+    // ANCHOR: test-pow
+    let base = Fp::rand();
+    for exp in [0, 1, 13] {
+        let circuit = PowCircuit {
+            base: Some(base),
+            exp,
+        };
+        let expected = (0..exp).fold(Fp::one(), |acc, _| acc * base);
+        let prover = MockProver::run(5, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(5, &circuit, vec![vec![expected * base]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+    // ANCHOR_END: test-pow
 
-        // Load our private values into the circuit.
-        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
-        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+    // ANCHOR: test-dot
+    // [1, 2, 3] . [4, 5, 6] = 32.
+    let to_witness = |values: &[u64]| -> Vec<Option<Fp>> {
+        values.iter().map(|&v| Some(Fp::from_u64(v))).collect()
+    };
+    let circuit = DotCircuit {
+        a: to_witness(&[1, 2, 3]),
+        b: to_witness(&[4, 5, 6]),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(32)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-        // Use `subtract` to get `d = a - b`.
-        let d = field_chip.subtract(&mut layouter, a, b)?;
+    let prover = MockProver::run(5, &circuit, vec![vec![Fp::from_u64(33)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The accumulator region copies in each operand once, and no products or
+    // partial sums, so the only other copy is the exposed result.
+    assert_eq!(count_layout(&circuit).unwrap().copies, 2 * 3 + 1);
 
-        // Expose the result as a public input to the circuit.
-        field_chip.expose_public(layouter.namespace(|| "expose d"), d, 0)
+    // Vectors of different lengths are rejected during synthesis.
+    let circuit = DotCircuit {
+        a: to_witness(&[1, 2, 3]),
+        b: to_witness(&[4, 5]),
+    };
+    assert!(MockProver::run(5, &circuit, vec![vec![Fp::zero()]]).is_err());
+    // ANCHOR_END: test-dot
+
+    // ANCHOR: test-sbox
+    // The S-box takes one load and three two-row gates.
+    const SBOX_MAX_ROWS: usize = 7;
+
+    let x = Fp::rand();
+    let circuit = SboxCircuit { x: Some(x) };
+    let rows = count_layout(&circuit).unwrap().rows;
+    assert!(
+        rows <= SBOX_MAX_ROWS,
+        "the S-box uses {} rows, more than {}",
+        rows,
+        SBOX_MAX_ROWS
+    );
+
+    let prover = MockProver::run(k, &circuit, vec![vec![x * x * x * x * x]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(k, &circuit, vec![vec![x * x * x * x]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-sbox
+
+    // ANCHOR: test-div
+    let b = Fp::rand();
+    let c = Fp::rand();
+    let a = c * b;
+    let circuit = DivCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![c]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(k, &circuit, vec![vec![c + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Dividing by zero has no satisfying witness, so synthesis fails.
+    let circuit = DivCircuit {
+        a: Some(a),
+        b: Some(Fp::zero()),
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).is_err());
+    // ANCHOR_END: test-div
+
+    // ANCHOR: test-on-line
+    // A point on `y = 3x + 5` verifies, and moving it off the line does not.
+    let m = Fp::from_u64(3);
+    let b = Fp::from_u64(5);
+    let x = Fp::rand();
+    let circuit = OnLineCircuit {
+        x: Some(x),
+        y: Some(m * x + b),
+        m,
+        b,
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let circuit = OnLineCircuit {
+        x: Some(x),
+        y: Some(m * x + b + Fp::one()),
+        m,
+        b,
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+    assert_fails_gate(&prover, "affine");
+    // ANCHOR_END: test-on-line
+
+    // ANCHOR: test-quadratic
+    // The roots 1 and 2 of `x^2 - 3x + 2` verify, and 3 does not.
+    let (a, b, c) = (Fp::one(), -Fp::from_u64(3), Fp::from_u64(2));
+    for x in [1, 2] {
+        let circuit = QuadraticCircuit {
+            x: Some(Fp::from_u64(x)),
+            a,
+            b,
+            c,
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
     }
-}
-// ANCHOR_END: circuit
 
-#[allow(clippy::many_single_char_names)]
-fn main() {
-    use halo2::{dev::MockProver, pasta::Fp};
+    let circuit = QuadraticCircuit {
+        x: Some(Fp::from_u64(3)),
+        a,
+        b,
+        c,
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+    assert_fails_gate(&prover, "quadratic");
+    // ANCHOR_END: test-quadratic
+
+    // ANCHOR: test-bit-reverse
+    // Each length-`n` input is exposed in bit-reversed order; swapping two
+    // outputs makes the proof fail.
+    for order in [&[0, 2, 1, 3][..], &[0, 4, 2, 6, 1, 5, 3, 7][..]] {
+        let values: Vec<Fp> = (0..order.len()).map(|_| Fp::rand()).collect();
+        let circuit = BitReverseCircuit {
+            values: values.iter().copied().map(Some).collect(),
+        };
+
+        let mut public_inputs: Vec<Fp> = order.iter().map(|&i| values[i]).collect();
+        let prover = MockProver::run(5, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        public_inputs.swap(1, 2);
+        let prover = MockProver::run(5, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+    // ANCHOR_END: test-bit-reverse
+
+    // ANCHOR: test-shuffle
+    // `output[i] = input[perm[i]]` satisfies the copy constraints; swapping two
+    // outputs does not.
+    let perm = vec![2, 0, 3, 1];
+    let input: Vec<Fp> = (0..perm.len()).map(|_| Fp::rand()).collect();
+    let mut output: Vec<Fp> = perm.iter().map(|&i| input[i]).collect();
+    let circuit = ShuffleCircuit {
+        input: input.iter().copied().map(Some).collect(),
+        output: output.iter().copied().map(Some).collect(),
+        perm: perm.clone(),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    // ANCHOR: test-circuit
-    // The number of rows in our circuit cannot exceed 2^k. Since our example
-    // circuit is very small, we can pick a very small value here.
-    let k = 4;
+    output.swap(0, 1);
+    let circuit = ShuffleCircuit {
+        input: input.iter().copied().map(Some).collect(),
+        output: output.iter().copied().map(Some).collect(),
+        perm,
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-shuffle
 
-    // Prepare the private and public inputs to the circuit!
+    // ANCHOR: test-assert-equal
     let a = Fp::rand();
-    let b = Fp::rand();
-    let d = a - b;
+    let circuit = AssertEqualCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    // Instantiate the circuit with the private inputs.
-    let circuit = MyCircuit {
+    let circuit = AssertEqualCircuit {
         a: Some(a),
-        b: Some(b),
+        b: Some(a + Fp::one()),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-assert-equal
+
+    // ANCHOR: test-checksum
+    // Data summing to its checksum verifies; tampering with any element, or
+    // with the checksum, does not.
+    let data: Vec<Fp> = (0..4).map(|_| Fp::rand()).collect();
+    let checksum = data.iter().fold(Fp::zero(), |acc, &value| acc + value);
+    let circuit = ChecksumCircuit {
+        data: data.iter().copied().map(Some).collect(),
+        expected: Some(checksum),
     };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
 
-    // Arrange the public input. We expose the subtraction result in row 0
-    // of the instance column, so we position it there in our public inputs.
-    let mut public_inputs = vec![d];
+    let mut tampered = data.clone();
+    tampered[1] += Fp::one();
+    let circuit = ChecksumCircuit {
+        data: tampered.into_iter().map(Some).collect(),
+        expected: Some(checksum),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert!(prover.verify().is_err());
 
-    // Given the correct public input, our circuit will verify.
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    let circuit = ChecksumCircuit {
+        data: data.into_iter().map(Some).collect(),
+        expected: Some(checksum + Fp::one()),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-checksum
+
+    // ANCHOR: test-fir
+    // Filtering [1, 2, 3, 4] with `y[i] = 2 * x[i] + 3 * x[i - 1]` gives [2, 7, 12, 17].
+    let circuit = FirCircuit {
+        values: [1, 2, 3, 4]
+            .iter()
+            .map(|&x| Some(Fp::from_u64(x)))
+            .collect(),
+        c0: Fp::from_u64(2),
+        c1: Fp::from_u64(3),
+    };
+    let mut public_inputs: Vec<Fp> = [2, 7, 12, 17].iter().map(|&y| Fp::from_u64(y)).collect();
+    let prover = MockProver::run(5, &circuit, vec![public_inputs.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 
-    // If we try some other public input, the proof will fail!
     public_inputs[0] += Fp::one();
-    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    let prover = MockProver::run(5, &circuit, vec![public_inputs]).unwrap();
     assert!(prover.verify().is_err());
-    // ANCHOR_END: test-circuit
-}
+    // ANCHOR_END: test-fir
+
+    // ANCHOR: test-secret-share
+    // Three shares of `p(x) = secret + 3x + 2x^2` reconstruct `p(0) = secret`.
+    let secret = Fp::rand();
+    let p = |x: Fp| secret + Fp::from_u64(3) * x + Fp::from_u64(2) * x * x;
+    let circuit = SecretShareCircuit {
+        shares: (1..=3)
+            .map(|x| {
+                let x = Fp::from_u64(x);
+                (x, Some(p(x)))
+            })
+            .collect(),
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![secret]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(5, &circuit, vec![vec![secret + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-secret-share
+
+    // ANCHOR: test-dual-op
+    // `sel = 1` selects `a + b` and `sel = 0` selects `a - b`.
+    let a = Fp::rand();
+    let b = Fp::rand();
+    for (sel, expected) in [(Fp::one(), a + b), (Fp::zero(), a - b)] {
+        let circuit = DualOpCircuit {
+            a: Some(a),
+            b: Some(b),
+            sel: Some(sel),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![expected + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 
+    // A non-boolean selector is rejected even though the arithmetic is consistent.
+    let sel = Fp::from_u64(2);
+    let circuit = DualOpCircuit {
+        a: Some(a),
+        b: Some(b),
+        sel: Some(sel),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![a - b + sel * (b + b)]]).unwrap();
+    assert_fails_gate(&prover, "dual op");
+    // ANCHOR_END: test-dual-op
+
+    // ANCHOR: test-poly-eval
+    // Opening a degree-2 polynomial at `z = 5` succeeds for the true evaluation
+    // and fails for any other claim.
+    let coeffs: Vec<Fp> = (0..3).map(|_| Fp::rand()).collect();
+    let z = Fp::from_u64(5);
+    let evaluation = coeffs[0] + coeffs[1] * z + coeffs[2] * z * z;
+    let circuit = PolyEvalCircuit {
+        coeffs: coeffs.iter().copied().map(Some).collect(),
+        claimed: Some(evaluation),
+        z,
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let circuit = PolyEvalCircuit {
+        coeffs: coeffs.iter().copied().map(Some).collect(),
+        claimed: Some(evaluation + Fp::one()),
+        z,
+    };
+    let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+    // ANCHOR_END: test-poly-eval
+}
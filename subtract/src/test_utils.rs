@@ -0,0 +1,132 @@
+//! Helpers for checking why `MockProver` rejects a circuit, and for counting
+//! what a circuit's layout uses.
+
+use halo2::{
+    arithmetic::FieldExt,
+    dev::{MockProver, VerifyFailure},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Selector,
+    },
+};
+
+/// Asserts that `prover` fails to verify, and that at least one failure is an
+/// unsatisfied constraint in the gate named `gate_name`.
+pub(crate) fn assert_fails_gate<F: FieldExt>(prover: &MockProver<F>, gate_name: &str) {
+    let failures = prover
+        .verify()
+        .expect_err("expected the circuit to fail verification");
+
+    // Constraint metadata only exposes the gate name through `Debug`, where it
+    // appears quoted.
+    let gate_name_debug = format!("{:?}", gate_name);
+    assert!(
+        failures.iter().any(|failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                format!("{:?}", constraint).contains(&gate_name_debug)
+            }
+            _ => false,
+        }),
+        "expected a failure in gate {:?}, got {:?}",
+        gate_name,
+        failures
+    );
+}
+
+/// The advice cells, copy constraints and rows used by a circuit's layout, as
+/// counted by [`count_layout`].
+#[derive(Debug, Default)]
+pub(crate) struct LayoutCount {
+    pub(crate) advice_cells: usize,
+    pub(crate) copies: usize,
+    pub(crate) rows: usize,
+}
+
+impl<F: FieldExt> Assignment<F> for LayoutCount {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.advice_cells += 1;
+        self.rows = self.rows.max(row + 1);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        self.copies += 1;
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Lays out `circuit` with its own floor planner and counts what it assigns.
+///
+/// No constant columns are passed to the floor planner, so circuits that
+/// assign cells from constants are not supported.
+pub(crate) fn count_layout<F: FieldExt, C: Circuit<F>>(circuit: &C) -> Result<LayoutCount, Error> {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+
+    let mut count = LayoutCount::default();
+    <C::FloorPlanner as FloorPlanner>::synthesize(&mut count, circuit, config, vec![])?;
+    Ok(count)
+}